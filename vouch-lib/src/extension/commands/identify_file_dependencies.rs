@@ -0,0 +1,25 @@
+use super::super::common;
+use anyhow::Result;
+use structopt::{self, StructOpt};
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct Arguments {
+    /// Dependencies specification file path.
+    #[structopt(name = "file-path")]
+    pub file_path: String,
+}
+
+pub fn run_command<T: common::Extension + std::fmt::Debug>(
+    args: &Arguments,
+    extension: &T,
+) -> Result<()> {
+    let file_path = std::path::PathBuf::from(&args.file_path);
+    let dependencies_spec = extension.identify_file_dependencies(&file_path)?;
+    println!("{}", serde_json::to_string(&dependencies_spec)?);
+    Ok(())
+}
@@ -2,8 +2,10 @@ use super::common;
 use anyhow::Result;
 use structopt::{self, StructOpt};
 
+mod identify_file_dependencies;
 mod identify_local_dependencies;
 mod registries_package_metadata;
+mod serve;
 mod static_data;
 
 #[derive(Debug, StructOpt, Clone)]
@@ -16,9 +18,17 @@ enum Command {
     #[structopt(name = "identify-local-dependencies")]
     IdentifyLocalDependencies(identify_local_dependencies::Arguments),
 
+    /// Identify dependencies from a given dependencies specification file.
+    #[structopt(name = "identify-file-dependencies")]
+    IdentifyFileDependencies(identify_file_dependencies::Arguments),
+
     /// Get package metadata from registries.
     #[structopt(name = "registries-package-metadata")]
     RegistriesPackageMetadata(registries_package_metadata::Arguments),
+
+    /// Serve commands as JSON-RPC over stdio until stdin closes.
+    #[structopt(name = "serve")]
+    Serve,
 }
 
 fn run_command<T: common::Extension + std::fmt::Debug>(
@@ -34,9 +44,17 @@ fn run_command<T: common::Extension + std::fmt::Debug>(
             identify_local_dependencies::run_command(&args, extension)?;
         }
 
+        Command::IdentifyFileDependencies(args) => {
+            identify_file_dependencies::run_command(&args, extension)?;
+        }
+
         Command::RegistriesPackageMetadata(args) => {
             registries_package_metadata::run_command(&args, extension)?;
         }
+
+        Command::Serve => {
+            serve::run_command(extension)?;
+        }
     }
     Ok(())
 }
@@ -0,0 +1,184 @@
+use super::super::{common, rpc};
+use super::static_data;
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// Handles JSON-RPC requests from stdin until stdin closes.
+pub fn run_command<T: common::Extension + std::fmt::Debug>(extension: &mut T) -> Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<rpc::Request>(&line) {
+            Ok(request) => handle_request(&request, extension),
+            Err(error) => rpc::Response::from_error(
+                serde_json::Value::Null,
+                rpc::PARSE_ERROR,
+                &error.to_string(),
+            ),
+        };
+
+        let mut stdout = stdout.lock();
+        writeln!(stdout, "{}", serde_json::to_string(&response)?)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+fn handle_request<T: common::Extension + std::fmt::Debug>(
+    request: &rpc::Request,
+    extension: &mut T,
+) -> rpc::Response {
+    let id = request.id.clone();
+    let result = match request.method.as_str() {
+        "static-data" => serde_json::to_value(static_data::get(extension)).map_err(Into::into),
+        "identify-local-dependencies" => {
+            let params: rpc::IdentifyLocalDependenciesParams = match get_params(request) {
+                Ok(params) => params,
+                Err(response) => return response,
+            };
+            extension
+                .identify_local_dependencies(&params.working_directory)
+                .and_then(|output| Ok(serde_json::to_value(output)?))
+        }
+        "identify-file-dependencies" => {
+            let params: rpc::IdentifyFileDependenciesParams = match get_params(request) {
+                Ok(params) => params,
+                Err(response) => return response,
+            };
+            extension
+                .identify_file_dependencies(&params.file_path)
+                .and_then(|output| Ok(serde_json::to_value(output)?))
+        }
+        "registries-package-metadata" => {
+            let params: rpc::RegistriesPackageMetadataParams = match get_params(request) {
+                Ok(params) => params,
+                Err(response) => return response,
+            };
+            extension
+                .registries_package_metadata(&params.package_name, &params.package_version)
+                .and_then(|output| Ok(serde_json::to_value(output)?))
+        }
+        method => {
+            return rpc::Response::from_error(
+                id,
+                rpc::METHOD_NOT_FOUND,
+                &format!("Unknown method: {}", method),
+            )
+        }
+    };
+
+    match result {
+        Ok(result) => rpc::Response::from_result(id, result),
+        Err(error) => rpc::Response::from_error(id, rpc::EXTENSION_ERROR, &error.to_string()),
+    }
+}
+
+/// Parses request parameters. Returns an error response for invalid parameters.
+fn get_params<P>(request: &rpc::Request) -> std::result::Result<P, rpc::Response>
+where
+    for<'de> P: serde::Deserialize<'de>,
+{
+    serde_json::from_value(request.params.clone()).map_err(|error| {
+        rpc::Response::from_error(
+            request.id.clone(),
+            rpc::INVALID_PARAMS,
+            &format!("Invalid params: {}", error),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestExtension;
+
+    impl common::Extension for TestExtension {
+        fn name(&self) -> String {
+            "test".to_string()
+        }
+
+        fn registries(&self) -> Vec<String> {
+            vec!["registry.test".to_string()]
+        }
+
+        fn identify_local_dependencies(
+            &self,
+            _working_directory: &std::path::PathBuf,
+        ) -> Result<Vec<common::DependenciesSpec>> {
+            Ok(Vec::new())
+        }
+
+        fn registries_package_metadata(
+            &self,
+            package_name: &str,
+            _package_version: &str,
+        ) -> Result<Vec<common::RegistryPackageMetadata>> {
+            Err(anyhow::format_err!("Package not found: {}", package_name))
+        }
+    }
+
+    fn get_request(method: &str, params: serde_json::Value) -> rpc::Request {
+        rpc::Request {
+            jsonrpc: "2.0".to_string(),
+            id: 7.into(),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    #[test]
+    fn test_handle_request_returns_result() {
+        let request = get_request(
+            "identify-local-dependencies",
+            serde_json::json!({"working_directory": "/project"}),
+        );
+        let response = handle_request(&request, &mut TestExtension);
+        assert_eq!(response.id, serde_json::json!(7));
+        assert_eq!(response.result, Some(serde_json::json!([])));
+        assert!(response.error.is_none());
+    }
+
+    #[test]
+    fn test_handle_request_returns_extension_error() {
+        let request = get_request(
+            "registries-package-metadata",
+            serde_json::json!({"package_name": "d3", "package_version": "4.10.0"}),
+        );
+        let error = handle_request(&request, &mut TestExtension).error.unwrap();
+        assert_eq!(error.code, rpc::EXTENSION_ERROR);
+        assert_eq!(error.message, "Package not found: d3");
+    }
+
+    #[test]
+    fn test_static_data_lists_default_capabilities() -> Result<()> {
+        let request = get_request("static-data", serde_json::Value::Null);
+        let response = handle_request(&request, &mut TestExtension);
+        let static_data: crate::extension::process::StaticData =
+            serde_json::from_value(response.result.unwrap())?;
+        assert_eq!(
+            static_data.get_capabilities(),
+            common::Capability::defaults()
+        );
+        assert!(!static_data
+            .get_capabilities()
+            .contains(&common::Capability::IdentifyFileDependencies));
+        Ok(())
+    }
+
+    #[test]
+    fn test_handle_request_rejects_invalid_requests() {
+        let request = get_request("registries-package-metadata", serde_json::json!({}));
+        let error = handle_request(&request, &mut TestExtension).error.unwrap();
+        assert_eq!(error.code, rpc::INVALID_PARAMS);
+
+        let request = get_request("unknown", serde_json::Value::Null);
+        let error = handle_request(&request, &mut TestExtension).error.unwrap();
+        assert_eq!(error.code, rpc::METHOD_NOT_FOUND);
+    }
+}
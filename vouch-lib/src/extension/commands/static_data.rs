@@ -1,13 +1,20 @@
 use super::super::common;
 use anyhow::Result;
 
+pub fn get<T: common::Extension + std::fmt::Debug>(
+    extension: &T,
+) -> crate::extension::process::StaticData {
+    crate::extension::process::StaticData {
+        name: extension.name(),
+        registry_host_names: extension.registries(),
+        version: extension.version(),
+        version_scheme: extension.version_scheme(),
+        protocol_version: crate::extension::process::PROTOCOL_VERSION,
+        capabilities: extension.capabilities().into_iter().collect(),
+    }
+}
+
 pub fn run_command<T: common::Extension + std::fmt::Debug>(extension: &T) -> Result<()> {
-    println!(
-        "{}",
-        serde_json::to_string(&crate::extension::process::StaticData {
-            name: extension.name(),
-            registry_host_names: extension.registries()
-        })?
-    );
+    println!("{}", serde_json::to_string(&get(extension))?);
     Ok(())
 }
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{format_err, Result};
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct VersionError(String);
@@ -49,6 +49,51 @@ pub struct RegistryPackageMetadata {
     pub is_primary: bool,
 }
 
+/// An optional extension command beyond static-data.
+#[derive(
+    Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Capability {
+    IdentifyLocalDependencies,
+    IdentifyFileDependencies,
+    RegistriesPackageMetadata,
+
+    /// Long-lived JSON-RPC mode over stdio.
+    Serve,
+
+    /// A capability which is not known to this version of the library.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Capability {
+    /// Returns the capabilities of an extension which implements only the required trait
+    /// methods. Serve is implemented by the library for every extension.
+    pub fn defaults() -> std::collections::BTreeSet<Self> {
+        vec![
+            Self::IdentifyLocalDependencies,
+            Self::RegistriesPackageMetadata,
+            Self::Serve,
+        ]
+        .into_iter()
+        .collect()
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Self::IdentifyLocalDependencies => "identify-local-dependencies",
+            Self::IdentifyFileDependencies => "identify-file-dependencies",
+            Self::RegistriesPackageMetadata => "registries-package-metadata",
+            Self::Serve => "serve",
+            Self::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 pub trait FromLib: Extension + Send + Sync {
     /// Initialize extension from a library.
     fn new() -> Self
@@ -57,10 +102,12 @@ pub trait FromLib: Extension + Send + Sync {
 }
 
 pub trait FromProcess: Extension + Send + Sync {
-    /// Initialize extension from a process.
+    /// Initialize extension from a process. The timeout limits each extension command. None
+    /// disables the time limit.
     fn from_process(
-        process_path: &std::path::PathBuf,
-        extension_config_path: &std::path::PathBuf,
+        process_path: &std::path::Path,
+        extension_config_path: &std::path::Path,
+        timeout: Option<std::time::Duration>,
     ) -> Result<Self>
     where
         Self: Sized;
@@ -73,12 +120,49 @@ pub trait Extension: Send + Sync {
     // Returns supported registries host names.
     fn registries(&self) -> Vec<String>;
 
+    /// Returns the supported optional commands.
+    ///
+    /// Extensions which implement `identify_file_dependencies` should add
+    /// `Capability::IdentifyFileDependencies` to the defaults.
+    fn capabilities(&self) -> std::collections::BTreeSet<Capability> {
+        Capability::defaults()
+    }
+
+    /// Returns the extension release version, if known.
+    fn version(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the version numbering scheme used by supported registries.
+    fn version_scheme(&self) -> super::VersionScheme {
+        super::VersionScheme::Semver
+    }
+
     /// Identify local package dependencies.
+    // Extension crates implement this signature.
+    #[allow(clippy::ptr_arg)]
     fn identify_local_dependencies(
         &self,
         working_directory: &std::path::PathBuf,
     ) -> Result<Vec<DependenciesSpec>>;
 
+    /// Identify dependencies from a given dependencies specification file.
+    ///
+    /// The file name may not be meaningful (for example, when read from stdin). In that case the
+    /// file format should be identified from the file content.
+    // Extension crates implement this signature.
+    #[allow(clippy::ptr_arg)]
+    fn identify_file_dependencies(
+        &self,
+        file_path: &std::path::PathBuf,
+    ) -> Result<DependenciesSpec> {
+        Err(format_err!(
+            "Extension {name} does not support dependencies file: {path}",
+            name = self.name(),
+            path = file_path.display()
+        ))
+    }
+
     /// Query package registries for package metadata.
     fn registries_package_metadata(
         &self,
@@ -1,7 +1,11 @@
 pub mod commands;
 pub mod common;
 pub mod process;
+pub mod rpc;
+pub mod version;
 
 pub use common::{
-    DependenciesSpec, Dependency, Extension, FromLib, FromProcess, RegistryPackageMetadata,
+    Capability, DependenciesSpec, Dependency, Extension, FromLib, FromProcess,
+    RegistryPackageMetadata,
 };
+pub use version::{Version, VersionDistance, VersionScheme};
@@ -1,11 +1,191 @@
 use anyhow::{format_err, Context, Result};
 
 use super::common;
+use super::rpc;
+
+/// Default time limit for an extension process command.
+pub const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Extension protocol version implemented by this library.
+///
+/// Incremented when a change to the protocol breaks compatibility between host and extension.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Library version which writes static data cache files. Caches written by other versions are
+/// refreshed.
+static LIBRARY_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct StaticData {
     pub name: String,
     pub registry_host_names: Vec<String>,
+
+    /// Extension release version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    #[serde(default)]
+    pub version_scheme: super::VersionScheme,
+
+    /// Extension protocol version. Zero if the extension predates protocol versioning.
+    #[serde(default)]
+    pub protocol_version: u32,
+
+    /// Supported optional commands. Ignored for protocol version zero.
+    #[serde(default)]
+    pub capabilities: Vec<common::Capability>,
+}
+
+impl StaticData {
+    /// Returns an error if the extension protocol version is newer than the host supports.
+    pub fn check_compatibility(&self) -> Result<()> {
+        if self.protocol_version > PROTOCOL_VERSION {
+            return Err(format_err!(
+                "Extension {name} uses protocol version {version}. \
+                Latest supported protocol version: {supported_version}. \
+                Please update vouch.",
+                name = self.name,
+                version = self.protocol_version,
+                supported_version = PROTOCOL_VERSION,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the supported capabilities known to the host.
+    ///
+    /// Extensions which predate protocol versioning support the original command set.
+    pub fn get_capabilities(&self) -> std::collections::BTreeSet<common::Capability> {
+        if self.protocol_version == 0 {
+            return vec![
+                common::Capability::IdentifyLocalDependencies,
+                common::Capability::RegistriesPackageMetadata,
+            ]
+            .into_iter()
+            .collect();
+        }
+        self.capabilities
+            .iter()
+            .filter(|capability| **capability != common::Capability::Unknown)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Identifies the extension binary which produced cached static data.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+struct BinaryStamp {
+    size: u64,
+    modified_seconds: u64,
+    modified_nanoseconds: u32,
+    hash: String,
+}
+
+impl BinaryStamp {
+    fn from_path(path: &std::path::Path) -> Result<Self> {
+        let (size, modified_seconds, modified_nanoseconds) = get_file_metadata(path)?;
+        Ok(Self {
+            size,
+            modified_seconds,
+            modified_nanoseconds,
+            hash: get_file_hash(path)?,
+        })
+    }
+
+    /// Returns true if the file size and modification time match. Does not read the file.
+    fn is_metadata_match(&self, path: &std::path::Path) -> Result<bool> {
+        let (size, modified_seconds, modified_nanoseconds) = get_file_metadata(path)?;
+        Ok(self.size == size
+            && self.modified_seconds == modified_seconds
+            && self.modified_nanoseconds == modified_nanoseconds)
+    }
+}
+
+/// Returns file size and modification time.
+fn get_file_metadata(path: &std::path::Path) -> Result<(u64, u64, u32)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok((metadata.len(), modified.as_secs(), modified.subsec_nanos()))
+}
+
+fn get_file_hash(path: &std::path::Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Extension static data cache file content.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StaticDataCache {
+    library_version: String,
+    binary: BinaryStamp,
+    static_data: StaticData,
+}
+
+/// Returns cached static data if the cache is up-to-date with the extension binary.
+///
+/// The binary is hashed only if its size or modification time changed.
+fn read_static_data_cache(
+    process_path: &std::path::Path,
+    cache_path: &std::path::Path,
+) -> Result<Option<StaticData>> {
+    if !cache_path.is_file() {
+        return Ok(None);
+    }
+    let file = std::fs::File::open(cache_path)?;
+    let reader = std::io::BufReader::new(file);
+    let mut cache: StaticDataCache = match serde_yaml::from_reader(reader) {
+        Ok(cache) => cache,
+        // Unversioned or corrupt cache file.
+        Err(_) => return Ok(None),
+    };
+    if cache.library_version != LIBRARY_VERSION {
+        return Ok(None);
+    }
+    if cache.binary.is_metadata_match(process_path)? {
+        return Ok(Some(cache.static_data));
+    }
+
+    let binary = BinaryStamp::from_path(process_path)?;
+    if binary.hash != cache.binary.hash {
+        return Ok(None);
+    }
+    // Same binary with new file metadata, for example after a copy.
+    cache.binary = binary;
+    write_static_data_cache(&cache, cache_path)?;
+    Ok(Some(cache.static_data))
+}
+
+fn write_static_data_cache(cache: &StaticDataCache, cache_path: &std::path::Path) -> Result<()> {
+    let file = std::fs::File::create(cache_path).context(format!(
+        "Can't open/create file for writing: {}",
+        cache_path.display()
+    ))?;
+    let writer = std::io::BufWriter::new(file);
+    serde_yaml::to_writer(writer, &cache)?;
+    Ok(())
+}
+
+/// Runs the extension static-data command and rewrites the cache file.
+fn refresh_static_data(
+    process_path: &std::path::Path,
+    cache_path: &std::path::Path,
+    timeout: Option<std::time::Duration>,
+) -> Result<StaticData> {
+    let binary = BinaryStamp::from_path(process_path)?;
+    let static_data: Box<StaticData> = run_process(process_path, &["static-data"], timeout)?;
+    let cache = StaticDataCache {
+        library_version: LIBRARY_VERSION.to_string(),
+        binary,
+        static_data: *static_data,
+    };
+    cache.static_data.check_compatibility()?;
+    write_static_data_cache(&cache, cache_path)?;
+    Ok(cache.static_data)
 }
 
 #[derive(Debug, Clone)]
@@ -13,42 +193,118 @@ pub struct ProcessExtension {
     process_path_: std::path::PathBuf,
     name_: String,
     registry_host_names_: Vec<String>,
+    version_: Option<String>,
+    version_scheme_: super::VersionScheme,
+    capabilities_: std::collections::BTreeSet<common::Capability>,
+    timeout_: Option<std::time::Duration>,
+
+    /// Long-lived JSON-RPC session. Started on first use if the extension supports serve.
+    session_: std::sync::Arc<std::sync::Mutex<Option<rpc::Client>>>,
+}
+
+impl ProcessExtension {
+    /// Initialize extension from a process. Ignores and rewrites the static data cache file.
+    pub fn refresh(
+        process_path: &std::path::Path,
+        extension_config_path: &std::path::Path,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<Self> {
+        let static_data = refresh_static_data(process_path, extension_config_path, timeout)?;
+        Ok(Self::from_static_data(process_path, static_data, timeout))
+    }
+
+    /// Returns the extension binary path.
+    pub fn process_path(&self) -> &std::path::PathBuf {
+        &self.process_path_
+    }
+
+    fn from_static_data(
+        process_path: &std::path::Path,
+        static_data: StaticData,
+        timeout: Option<std::time::Duration>,
+    ) -> Self {
+        ProcessExtension {
+            process_path_: process_path.to_path_buf(),
+            capabilities_: static_data.get_capabilities(),
+            name_: static_data.name,
+            registry_host_names_: static_data.registry_host_names,
+            version_: static_data.version,
+            version_scheme_: static_data.version_scheme,
+            timeout_: timeout,
+            session_: std::sync::Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Runs an extension command.
+    ///
+    /// Uses the JSON-RPC session if the extension supports serve. Otherwise runs a new process
+    /// with the given arguments.
+    fn call<T>(&self, method: &str, params: serde_json::Value, args: &[&str]) -> Result<T>
+    where
+        for<'de> T: serde::Deserialize<'de>,
+    {
+        if !self.capabilities_.contains(&common::Capability::Serve) {
+            let output: Box<T> = run_process(&self.process_path_, args, self.timeout_)?;
+            return Ok(*output);
+        }
+
+        let mut session = self
+            .session_
+            .lock()
+            .map_err(|_| format_err!("Extension {} session lock poisoned.", self.name_))?;
+        if session.is_none() {
+            *session = Some(rpc::Client::start(&self.process_path_, &["serve"])?);
+        }
+        let client = session
+            .as_mut()
+            .ok_or(format_err!("Extension {} session missing.", self.name_))?;
+
+        let result = client.call(method, params, self.timeout_);
+        let is_session_usable = match &result {
+            Ok(_) => true,
+            Err(error) => matches!(
+                error
+                    .downcast_ref::<ProcessError>()
+                    .map(|error| &error.kind),
+                Some(ProcessErrorKind::Rpc { .. })
+            ),
+        };
+        if !is_session_usable {
+            // Stop the process. The next call starts a new session.
+            *session = None;
+        }
+
+        let result = result?;
+        Ok(
+            serde_json::from_value(result).map_err(|error| ProcessError {
+                process_path: self.process_path_.clone(),
+                command: method.to_string(),
+                kind: ProcessErrorKind::InvalidOutput(error.to_string()),
+                stderr: String::new(),
+            })?,
+        )
+    }
 }
 
 impl common::FromProcess for ProcessExtension {
+    /// Initialize extension from a process. Static data is read from the cache file unless the
+    /// extension binary changed since the cache was written.
     fn from_process(
-        process_path: &std::path::PathBuf,
-        extension_config_path: &std::path::PathBuf,
+        process_path: &std::path::Path,
+        extension_config_path: &std::path::Path,
+        timeout: Option<std::time::Duration>,
     ) -> Result<Self>
     where
         Self: Sized,
     {
-        let static_data: StaticData = if extension_config_path.is_file() {
-            let file = std::fs::File::open(&extension_config_path)?;
-            let reader = std::io::BufReader::new(file);
-            serde_yaml::from_reader(reader)?
-        } else {
-            let static_data: Box<StaticData> = run_process(&process_path, &vec!["static-data"])?;
-            let static_data = *static_data;
-
-            let file = std::fs::OpenOptions::new()
-                .write(true)
-                .create(true)
-                .open(&extension_config_path)
-                .context(format!(
-                    "Can't open/create file for writing: {}",
-                    extension_config_path.display()
-                ))?;
-            let writer = std::io::BufWriter::new(file);
-            serde_yaml::to_writer(writer, &static_data)?;
-            static_data
+        let static_data = match read_static_data_cache(process_path, extension_config_path)? {
+            Some(static_data) => {
+                static_data.check_compatibility()?;
+                static_data
+            }
+            None => refresh_static_data(process_path, extension_config_path, timeout)?,
         };
-
-        Ok(ProcessExtension {
-            process_path_: process_path.clone(),
-            name_: static_data.name,
-            registry_host_names_: static_data.registry_host_names,
-        })
+        Ok(Self::from_static_data(process_path, static_data, timeout))
     }
 }
 
@@ -61,18 +317,64 @@ impl common::Extension for ProcessExtension {
         self.registry_host_names_.clone()
     }
 
+    fn version(&self) -> Option<String> {
+        self.version_.clone()
+    }
+
+    fn version_scheme(&self) -> super::VersionScheme {
+        self.version_scheme_
+    }
+
+    fn capabilities(&self) -> std::collections::BTreeSet<common::Capability> {
+        self.capabilities_.clone()
+    }
+
     /// Returns a list of local package dependencies specification files.
     fn identify_local_dependencies(
         &self,
         working_directory: &std::path::PathBuf,
     ) -> Result<Vec<common::DependenciesSpec>> {
+        if !self
+            .capabilities_
+            .contains(&common::Capability::IdentifyLocalDependencies)
+        {
+            return Ok(Vec::new());
+        }
+        let params = serde_json::to_value(rpc::IdentifyLocalDependenciesParams {
+            working_directory: working_directory.clone(),
+        })?;
         let working_directory = working_directory.to_str().ok_or(format_err!(
             "Failed to parse path into string: {}",
             working_directory.display()
         ))?;
         let args = vec!["identify-local-dependencies", working_directory];
-        let output: Box<Vec<common::DependenciesSpec>> = run_process(&self.process_path_, &args)?;
-        Ok(*output)
+        self.call("identify-local-dependencies", params, &args)
+    }
+
+    /// Returns the dependencies specified within the given dependencies specification file.
+    fn identify_file_dependencies(
+        &self,
+        file_path: &std::path::PathBuf,
+    ) -> Result<common::DependenciesSpec> {
+        if !self
+            .capabilities_
+            .contains(&common::Capability::IdentifyFileDependencies)
+        {
+            return Err(format_err!(
+                "Extension {name} does not support dependencies file: {path}",
+                name = self.name_,
+                path = file_path.display()
+            ));
+        }
+        let params = serde_json::to_value(rpc::IdentifyFileDependenciesParams {
+            file_path: file_path.clone(),
+        })?;
+        let file_path = file_path.to_str().ok_or(format_err!(
+            "Failed to parse path into string: {}",
+            file_path.display()
+        ))?;
+        let args = vec!["identify-file-dependencies", file_path];
+        self.call("identify-file-dependencies", params, &args)
     }
 
     /// Given a package name and version, queries the remote registry for package metadata.
@@ -81,29 +383,404 @@ impl common::Extension for ProcessExtension {
         package_name: &str,
         package_version: &str,
     ) -> Result<Vec<common::RegistryPackageMetadata>> {
+        if !self
+            .capabilities_
+            .contains(&common::Capability::RegistriesPackageMetadata)
+        {
+            return Ok(Vec::new());
+        }
+        let params = serde_json::to_value(rpc::RegistriesPackageMetadataParams {
+            package_name: package_name.to_string(),
+            package_version: package_version.to_string(),
+        })?;
         let args = vec!["registries-package-metadata", package_name, package_version];
-        let output: Box<Vec<common::RegistryPackageMetadata>> =
-            run_process(&self.process_path_, &args)?;
-        Ok(*output)
+        self.call("registries-package-metadata", params, &args)
+    }
+}
+
+/// Reason for an extension process failure.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ProcessErrorKind {
+    /// The process exited unsuccessfully. The exit code is None if the process was killed by a
+    /// signal.
+    Exit(Option<i32>),
+
+    /// The process did not exit within the timeout and was killed.
+    Timeout(std::time::Duration),
+
+    /// The process output could not be parsed.
+    InvalidOutput(String),
+
+    /// The extension returned a JSON-RPC error response.
+    Rpc { code: i64, message: String },
+}
+
+/// An extension process failure.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProcessError {
+    pub process_path: std::path::PathBuf,
+    pub command: String,
+    pub kind: ProcessErrorKind,
+
+    /// Standard error output of the process.
+    pub stderr: String,
+}
+
+impl ProcessError {
+    /// Returns the error message written by the extension, if any.
+    pub fn message(&self) -> Option<String> {
+        if let ProcessErrorKind::Rpc { message, .. } = &self.kind {
+            return Some(message.clone());
+        }
+        let message = self.stderr.trim();
+        if message.is_empty() {
+            None
+        } else {
+            Some(message.to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let process = self
+            .process_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(self.process_path.display().to_string());
+        match &self.kind {
+            ProcessErrorKind::Exit(Some(code)) => write!(
+                f,
+                "{} {} failed with exit code {}",
+                process, self.command, code
+            )?,
+            ProcessErrorKind::Exit(None) => {
+                write!(f, "{} {} was terminated", process, self.command)?
+            }
+            ProcessErrorKind::Timeout(timeout) => write!(
+                f,
+                "{} {} timed out after {} seconds",
+                process,
+                self.command,
+                timeout.as_secs_f64()
+            )?,
+            ProcessErrorKind::Rpc { code, .. } => write!(
+                f,
+                "{} {} failed with error code {}",
+                process, self.command, code
+            )?,
+            ProcessErrorKind::InvalidOutput(error) => write!(
+                f,
+                "{} {} returned invalid output: {}",
+                process, self.command, error
+            )?,
+        }
+        if let Some(message) = self.message() {
+            write!(f, "\n{}", message)?;
+        }
+        Ok(())
     }
 }
 
-fn run_process<'a, T: ?Sized>(process_path: &std::path::PathBuf, args: &Vec<&str>) -> Result<Box<T>>
+impl std::error::Error for ProcessError {}
+
+fn run_process<T>(
+    process_path: &std::path::Path,
+    args: &[&str],
+    timeout: Option<std::time::Duration>,
+) -> Result<Box<T>>
 where
-    for<'de> T: serde::Deserialize<'de> + 'a,
+    for<'de> T: serde::Deserialize<'de>,
 {
     let process = process_path.to_str().ok_or(format_err!(
         "Failed to parse string from process path: {}",
         process_path.display()
     ))?;
-    let handle = std::process::Command::new(process)
+    let mut child = std::process::Command::new(process)
         .args(args)
         .stdin(std::process::Stdio::null())
         .stderr(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
-        .output()?;
+        .spawn()
+        .context(format!("Failed to start extension process: {}", process))?;
+
+    // Read pipes concurrently so that a full pipe can not block the process.
+    let stdout_reader = read_pipe(child.stdout.take());
+    let stderr_reader = read_pipe(child.stderr.take());
+
+    let get_error = |kind: ProcessErrorKind, stderr: &[u8]| ProcessError {
+        process_path: process_path.to_path_buf(),
+        command: args.first().unwrap_or(&"").to_string(),
+        kind,
+        stderr: String::from_utf8_lossy(stderr).to_string(),
+    };
 
-    let stdout = String::from_utf8_lossy(&handle.stdout);
-    let output = serde_json::from_str(&stdout)?;
+    let status = match wait(&mut child, timeout)? {
+        Some(status) => status,
+        None => {
+            // Pipe readers are abandoned: descendant processes may still hold the pipes open.
+            let timeout = timeout.unwrap_or_default();
+            return Err(get_error(ProcessErrorKind::Timeout(timeout), &[]).into());
+        }
+    };
+    let stdout = stdout_reader
+        .join()
+        .map_err(|_| format_err!("Failed to read extension process stdout."))?;
+    let stderr = stderr_reader
+        .join()
+        .map_err(|_| format_err!("Failed to read extension process stderr."))?;
+
+    if !status.success() {
+        return Err(get_error(ProcessErrorKind::Exit(status.code()), &stderr).into());
+    }
+
+    let stdout = String::from_utf8_lossy(&stdout);
+    let output = serde_json::from_str(&stdout)
+        .map_err(|error| get_error(ProcessErrorKind::InvalidOutput(error.to_string()), &stderr))?;
     Ok(Box::new(output))
 }
+
+/// Reads the given pipe to the end on a separate thread.
+fn read_pipe<R: std::io::Read + Send + 'static>(
+    pipe: Option<R>,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut content = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut content);
+        }
+        content
+    })
+}
+
+/// Waits for the process to exit. Kills the process and returns None on timeout.
+fn wait(
+    child: &mut std::process::Child,
+    timeout: Option<std::time::Duration>,
+) -> Result<Option<std::process::ExitStatus>> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Ok(Some(child.wait()?)),
+    };
+
+    let start = std::time::Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if start.elapsed() >= timeout {
+            // The process may exit between the check and the kill.
+            let _ = child.kill();
+            child.wait()?;
+            return Ok(None);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_static_data(json: &str) -> Result<StaticData> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    #[test]
+    fn test_unversioned_extension_has_original_capabilities() -> Result<()> {
+        let static_data =
+            get_static_data(r#"{"name": "py", "registry_host_names": ["pypi.org"]}"#)?;
+        static_data.check_compatibility()?;
+
+        let expected: std::collections::BTreeSet<_> = vec![
+            common::Capability::IdentifyLocalDependencies,
+            common::Capability::RegistriesPackageMetadata,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(static_data.get_capabilities(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_capabilities_are_ignored() -> Result<()> {
+        let static_data = get_static_data(
+            r#"{
+                "name": "py",
+                "registry_host_names": ["pypi.org"],
+                "protocol_version": 1,
+                "capabilities": ["identify-file-dependencies", "some-future-command"]
+            }"#,
+        )?;
+        static_data.check_compatibility()?;
+
+        let expected: std::collections::BTreeSet<_> =
+            vec![common::Capability::IdentifyFileDependencies]
+                .into_iter()
+                .collect();
+        assert_eq!(static_data.get_capabilities(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_protocol_version_is_incompatible() -> Result<()> {
+        let static_data = get_static_data(&format!(
+            r#"{{"name": "py", "registry_host_names": [], "protocol_version": {}}}"#,
+            PROTOCOL_VERSION + 1
+        ))?;
+        assert!(static_data.check_compatibility().is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn run_shell(script: &str, timeout: Option<std::time::Duration>) -> Result<Vec<String>> {
+        let output: Box<Vec<String>> =
+            run_process(std::path::Path::new("sh"), &["-c", script], timeout)?;
+        Ok(*output)
+    }
+
+    #[cfg(unix)]
+    fn get_process_error<T: std::fmt::Debug>(result: Result<T>) -> ProcessError {
+        result
+            .unwrap_err()
+            .downcast::<ProcessError>()
+            .expect("Expected process error.")
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_parses_output() -> Result<()> {
+        let result = run_shell(r#"echo '["a", "b"]'"#, Some(DEFAULT_TIMEOUT))?;
+        assert_eq!(result, vec!["a".to_string(), "b".to_string()]);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_reports_exit_code_and_stderr() {
+        let error = get_process_error(run_shell(
+            "echo 'Registry unavailable.' >&2; exit 3",
+            Some(DEFAULT_TIMEOUT),
+        ));
+        assert_eq!(error.kind, ProcessErrorKind::Exit(Some(3)));
+        assert_eq!(error.message(), Some("Registry unavailable.".to_string()));
+        assert!(error.to_string().contains("Registry unavailable."));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_reports_invalid_output() {
+        let error = get_process_error(run_shell("echo 'not json'", Some(DEFAULT_TIMEOUT)));
+        assert!(matches!(error.kind, ProcessErrorKind::InvalidOutput(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_process_kills_process_on_timeout() {
+        let timeout = std::time::Duration::from_millis(100);
+        let start = std::time::Instant::now();
+        let error = get_process_error(run_shell("sleep 5", Some(timeout)));
+        assert_eq!(error.kind, ProcessErrorKind::Timeout(timeout));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    /// Writes an extension script which reports the given registry and counts its runs.
+    #[cfg(unix)]
+    fn write_extension(directory: &std::path::Path, registry: &str) -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let path = directory.join("vouch-cache");
+        std::fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\necho run >> {runs}\n\
+                echo '{{\"name\": \"cache\", \"registry_host_names\": [\"{registry}\"]}}'\n",
+                runs = directory.join("runs").display(),
+                registry = registry
+            ),
+        )?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn get_run_count(directory: &std::path::Path) -> usize {
+        std::fs::read_to_string(directory.join("runs"))
+            .map(|runs| runs.lines().count())
+            .unwrap_or(0)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_static_data_cache_is_refreshed_when_binary_changes() -> Result<()> {
+        use common::{Extension, FromProcess};
+        let tmp_dir = tempdir::TempDir::new("vouch_lib_test_static_data_cache")?;
+        let directory = tmp_dir.path().to_path_buf();
+        let process_path = directory.join("vouch-cache");
+        let cache_path = directory.join("cache.yaml");
+
+        write_extension(&directory, "one.example")?;
+        let extension =
+            ProcessExtension::from_process(&process_path, &cache_path, Some(DEFAULT_TIMEOUT))?;
+        assert_eq!(extension.registries(), vec!["one.example".to_string()]);
+        ProcessExtension::from_process(&process_path, &cache_path, Some(DEFAULT_TIMEOUT))?;
+        assert_eq!(get_run_count(&directory), 1);
+
+        write_extension(&directory, "second.example")?;
+        let extension =
+            ProcessExtension::from_process(&process_path, &cache_path, Some(DEFAULT_TIMEOUT))?;
+        assert_eq!(extension.registries(), vec!["second.example".to_string()]);
+        assert_eq!(get_run_count(&directory), 2);
+
+        ProcessExtension::refresh(&process_path, &cache_path, Some(DEFAULT_TIMEOUT))?;
+        assert_eq!(get_run_count(&directory), 3);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_static_data_command_uses_timeout() -> Result<()> {
+        use common::FromProcess;
+        use std::os::unix::fs::PermissionsExt;
+        let tmp_dir = tempdir::TempDir::new("vouch_lib_test_static_data_timeout")?;
+        let directory = tmp_dir.path().to_path_buf();
+        let process_path = directory.join("vouch-slow");
+        let cache_path = directory.join("cache.yaml");
+        std::fs::write(
+            &process_path,
+            "#!/bin/sh
+sleep 5
+",
+        )?;
+        std::fs::set_permissions(&process_path, std::fs::Permissions::from_mode(0o755))?;
+
+        let timeout = std::time::Duration::from_millis(100);
+        let error = get_process_error(ProcessExtension::from_process(
+            &process_path,
+            &cache_path,
+            Some(timeout),
+        ));
+        assert_eq!(error.kind, ProcessErrorKind::Timeout(timeout));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unversioned_static_data_cache_is_refreshed() -> Result<()> {
+        use common::{Extension, FromProcess};
+        let tmp_dir = tempdir::TempDir::new("vouch_lib_test_unversioned_cache")?;
+        let directory = tmp_dir.path().to_path_buf();
+        let process_path = directory.join("vouch-cache");
+        let cache_path = directory.join("cache.yaml");
+
+        write_extension(&directory, "new.example")?;
+        std::fs::write(
+            &cache_path,
+            "---\nname: cache\nregistry_host_names:\n  - old.example\n",
+        )?;
+        let extension =
+            ProcessExtension::from_process(&process_path, &cache_path, Some(DEFAULT_TIMEOUT))?;
+        assert_eq!(extension.registries(), vec!["new.example".to_string()]);
+        assert_eq!(get_run_count(&directory), 1);
+        Ok(())
+    }
+}
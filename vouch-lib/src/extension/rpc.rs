@@ -0,0 +1,342 @@
+//! JSON-RPC 2.0 over stdio for long-lived extension processes.
+//!
+//! Messages are newline-delimited JSON objects. The host writes requests to the extension's
+//! stdin and reads responses from its stdout. The extension exits when its stdin closes.
+use anyhow::{format_err, Context, Result};
+use std::io::{BufRead, Write};
+
+use super::process::{ProcessError, ProcessErrorKind};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Error code for failures reported by the extension while handling a request.
+pub const EXTENSION_ERROR: i64 = -32000;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    pub id: serde_json::Value,
+    pub method: String,
+
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: serde_json::Value,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+impl Response {
+    pub fn from_result(id: serde_json::Value, result: serde_json::Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn from_error(id: serde_json::Value, code: i64, message: &str) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(ResponseError {
+                code,
+                message: message.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ResponseError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IdentifyLocalDependenciesParams {
+    pub working_directory: std::path::PathBuf,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IdentifyFileDependenciesParams {
+    pub file_path: std::path::PathBuf,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RegistriesPackageMetadataParams {
+    pub package_name: String,
+    pub package_version: String,
+}
+
+/// Host side of a JSON-RPC session with an extension process.
+///
+/// The extension process is stopped when the client is dropped.
+#[derive(Debug)]
+pub struct Client {
+    process_path: std::path::PathBuf,
+    child: std::process::Child,
+    stdin: Option<std::process::ChildStdin>,
+    responses: std::sync::mpsc::Receiver<String>,
+    stderr: std::sync::Arc<std::sync::Mutex<Vec<u8>>>,
+    next_id: u64,
+}
+
+impl Client {
+    /// Starts the extension process with the given arguments.
+    pub fn start(process_path: &std::path::PathBuf, args: &[&str]) -> Result<Self> {
+        let mut child = std::process::Command::new(process_path)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .context(format!(
+                "Failed to start extension process: {}",
+                process_path.display()
+            ))?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(format_err!("Failed to open extension process stdout."))?;
+        let (sender, responses) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => {
+                        if sender.send(line).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let stderr = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        if let Some(mut child_stderr) = child.stderr.take() {
+            let stderr = stderr.clone();
+            std::thread::spawn(move || {
+                let mut buffer = [0; 1024];
+                while let Ok(count) = std::io::Read::read(&mut child_stderr, &mut buffer) {
+                    if count == 0 {
+                        break;
+                    }
+                    if let Ok(mut stderr) = stderr.lock() {
+                        stderr.extend_from_slice(&buffer[..count]);
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            process_path: process_path.clone(),
+            stdin: child.stdin.take(),
+            child,
+            responses,
+            stderr,
+            next_id: 1,
+        })
+    }
+
+    /// Calls the given method and returns its result.
+    ///
+    /// Failures are returned as ProcessError. The session should not be reused after a failure
+    /// other than ProcessErrorKind::Rpc.
+    pub fn call(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<serde_json::Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = Request {
+            jsonrpc: "2.0".to_string(),
+            id: id.into(),
+            method: method.to_string(),
+            params,
+        };
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or(format_err!("Extension JSON-RPC session is closed."))?;
+        let written =
+            writeln!(stdin, "{}", serde_json::to_string(&request)?).and_then(|_| stdin.flush());
+        if written.is_err() {
+            return Err(self.get_exit_error(method).into());
+        }
+
+        let start = std::time::Instant::now();
+        loop {
+            let line = match timeout {
+                Some(timeout) => {
+                    let remaining = timeout
+                        .checked_sub(start.elapsed())
+                        .unwrap_or(std::time::Duration::from_secs(0));
+                    match self.responses.recv_timeout(remaining) {
+                        Ok(line) => line,
+                        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                            return Err(self
+                                .get_error(method, ProcessErrorKind::Timeout(timeout))
+                                .into());
+                        }
+                        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                            return Err(self.get_exit_error(method).into());
+                        }
+                    }
+                }
+                None => match self.responses.recv() {
+                    Ok(line) => line,
+                    Err(_) => return Err(self.get_exit_error(method).into()),
+                },
+            };
+
+            let response: Response = match serde_json::from_str(&line) {
+                Ok(response) => response,
+                Err(error) => {
+                    return Err(self
+                        .get_error(method, ProcessErrorKind::InvalidOutput(error.to_string()))
+                        .into());
+                }
+            };
+            if response.id != id {
+                // Response to an earlier request which timed out.
+                continue;
+            }
+
+            if let Some(error) = response.error {
+                return Err(self
+                    .get_error(
+                        method,
+                        ProcessErrorKind::Rpc {
+                            code: error.code,
+                            message: error.message,
+                        },
+                    )
+                    .into());
+            }
+            return Ok(response.result.unwrap_or(serde_json::Value::Null));
+        }
+    }
+
+    fn get_error(&self, method: &str, kind: ProcessErrorKind) -> ProcessError {
+        let stderr = self
+            .stderr
+            .lock()
+            .map(|stderr| String::from_utf8_lossy(&stderr).to_string())
+            .unwrap_or_default();
+        ProcessError {
+            process_path: self.process_path.clone(),
+            command: method.to_string(),
+            kind,
+            stderr,
+        }
+    }
+
+    /// Returns an error for a process which closed its stdout.
+    fn get_exit_error(&mut self, method: &str) -> ProcessError {
+        let code = match wait(&mut self.child, std::time::Duration::from_secs(1)) {
+            Some(status) => status.code(),
+            None => None,
+        };
+        // Give the stderr reader a moment to collect the final output.
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        self.get_error(method, ProcessErrorKind::Exit(code))
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        // Closing stdin asks the extension to exit.
+        self.stdin.take();
+        if wait(&mut self.child, std::time::Duration::from_secs(1)).is_none() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// Waits up to the given duration for the process to exit.
+fn wait(
+    child: &mut std::process::Child,
+    timeout: std::time::Duration,
+) -> Option<std::process::ExitStatus> {
+    let start = std::time::Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) if start.elapsed() < timeout => {
+                std::thread::sleep(std::time::Duration::from_millis(10))
+            }
+            _ => return None,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn start_shell(script: &str) -> Result<Client> {
+        Client::start(&std::path::PathBuf::from("sh"), &["-c", script])
+    }
+
+    #[test]
+    fn test_call_returns_result() -> Result<()> {
+        let mut client = start_shell(
+            r#"while read request; do
+                echo '{"jsonrpc": "2.0", "id": 1, "result": ["first"]}'
+            done"#,
+        )?;
+        let result = client.call("static-data", serde_json::Value::Null, None)?;
+        assert_eq!(result, serde_json::json!(["first"]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_reports_rpc_error() -> Result<()> {
+        let mut client = start_shell(
+            r#"read request
+            echo '{"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "Registry unavailable."}}'
+            cat > /dev/null"#,
+        )?;
+        let error = client
+            .call("registries-package-metadata", serde_json::Value::Null, None)
+            .unwrap_err()
+            .downcast::<ProcessError>()
+            .expect("Expected process error.");
+        assert_eq!(error.message(), Some("Registry unavailable.".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_call_reports_process_exit() -> Result<()> {
+        let mut client = start_shell("read request; echo 'Crashed.' >&2; exit 3")?;
+        let error = client
+            .call(
+                "identify-local-dependencies",
+                serde_json::Value::Null,
+                Some(std::time::Duration::from_secs(5)),
+            )
+            .unwrap_err()
+            .downcast::<ProcessError>()
+            .expect("Expected process error.");
+        assert_eq!(error.kind, ProcessErrorKind::Exit(Some(3)));
+        Ok(())
+    }
+}
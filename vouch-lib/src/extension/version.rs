@@ -0,0 +1,257 @@
+use anyhow::{format_err, Result};
+
+/// Package version numbering scheme used by an ecosystem.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum VersionScheme {
+    /// Semantic versioning. Example: 1.2.3-beta.1
+    #[default]
+    #[serde(rename = "semver")]
+    Semver,
+
+    /// Python PEP 440 versioning. Example: 1!1.2.3rc1.post2
+    #[serde(rename = "pep440")]
+    Pep440,
+}
+
+impl std::str::FromStr for VersionScheme {
+    type Err = anyhow::Error;
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "semver" => Ok(VersionScheme::Semver),
+            "pep440" => Ok(VersionScheme::Pep440),
+            _ => Err(format_err!("Unknown version scheme: {}", value)),
+        }
+    }
+}
+
+/// Difference between two versions. Ordered from nearest to furthest.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum VersionDistance {
+    #[default]
+    #[serde(rename = "exact")]
+    Exact,
+
+    #[serde(rename = "patch")]
+    Patch,
+
+    #[serde(rename = "minor")]
+    Minor,
+
+    #[serde(rename = "major")]
+    Major,
+}
+
+impl std::fmt::Display for VersionDistance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            VersionDistance::Exact => "exact",
+            VersionDistance::Patch => "patch",
+            VersionDistance::Minor => "minor",
+            VersionDistance::Major => "major",
+        };
+        write!(f, "{}", value)
+    }
+}
+
+impl std::str::FromStr for VersionDistance {
+    type Err = anyhow::Error;
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "exact" => Ok(VersionDistance::Exact),
+            "patch" => Ok(VersionDistance::Patch),
+            "minor" => Ok(VersionDistance::Minor),
+            "major" => Ok(VersionDistance::Major),
+            _ => Err(format_err!(
+                "Expected value: `exact`, `patch`, `minor` or `major`. Found: {}",
+                value
+            )),
+        }
+    }
+}
+
+/// A parsed package version.
+///
+/// Only the release components and any pre, post or development release label are retained. Build
+/// metadata and local version labels are ignored.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Version {
+    /// PEP 440 epoch. Always zero for semver.
+    pub epoch: u64,
+
+    /// Release components. Example: [1, 2, 3] for 1.2.3
+    pub release: Vec<u64>,
+
+    /// Pre, post or development release label. Example: "rc1"
+    pub label: Option<String>,
+}
+
+impl Version {
+    /// Parse a version string using the given scheme.
+    pub fn parse(value: &str, scheme: &VersionScheme) -> Result<Self> {
+        match scheme {
+            VersionScheme::Semver => parse_semver(value),
+            VersionScheme::Pep440 => parse_pep440(value),
+        }
+    }
+
+    fn get_component(&self, index: usize) -> u64 {
+        self.release.get(index).cloned().unwrap_or(0)
+    }
+
+    /// Returns the distance between this version and another version.
+    ///
+    /// Versions which differ only by release label are a patch distance apart.
+    pub fn distance(&self, other: &Self) -> VersionDistance {
+        if self.epoch != other.epoch || self.get_component(0) != other.get_component(0) {
+            return VersionDistance::Major;
+        }
+        // Before 1.0.0 a minor increment is considered a major change.
+        let is_unstable = self.get_component(0) == 0;
+        if self.get_component(1) != other.get_component(1) {
+            return if is_unstable {
+                VersionDistance::Major
+            } else {
+                VersionDistance::Minor
+            };
+        }
+        let release_length = std::cmp::max(self.release.len(), other.release.len());
+        let same_release = (2..release_length)
+            .all(|index| self.get_component(index) == other.get_component(index));
+        if same_release && self.label == other.label {
+            return VersionDistance::Exact;
+        }
+        if is_unstable && self.get_component(1) == 0 {
+            return VersionDistance::Minor;
+        }
+        VersionDistance::Patch
+    }
+}
+
+fn parse_release(value: &str, original: &str) -> Result<Vec<u64>> {
+    value
+        .split('.')
+        .map(|component| {
+            component
+                .parse::<u64>()
+                .map_err(|_| format_err!("Failed to parse version: {}", original))
+        })
+        .collect()
+}
+
+fn parse_semver(value: &str) -> Result<Version> {
+    let version = value.trim().trim_start_matches(['v', '=']);
+    let version = version.split('+').next().unwrap_or("");
+    let mut parts = version.splitn(2, '-');
+    let release = parse_release(parts.next().unwrap_or(""), value)?;
+    let label = parts.next().map(|label| label.to_string());
+    Ok(Version {
+        epoch: 0,
+        release,
+        label,
+    })
+}
+
+fn parse_pep440(value: &str) -> Result<Version> {
+    let version = value.trim().to_lowercase();
+    let version = version.trim_start_matches('v');
+    let version = version.split('+').next().unwrap_or("");
+
+    let (epoch, version) = match version.find('!') {
+        Some(index) => (
+            version[..index]
+                .parse::<u64>()
+                .map_err(|_| format_err!("Failed to parse version epoch: {}", value))?,
+            &version[index + 1..],
+        ),
+        None => (0, version),
+    };
+
+    // Release segment ends at the first character which is not a digit or dot.
+    let release_end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    let release = parse_release(version[..release_end].trim_end_matches('.'), value)?;
+
+    let suffix = version[release_end..].trim_start_matches(['.', '-', '_']);
+    let label = if suffix.is_empty() {
+        None
+    } else {
+        Some(suffix.to_string())
+    };
+    Ok(Version {
+        epoch,
+        release,
+        label,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semver_distance() -> Result<()> {
+        let version = Version::parse("1.2.4", &VersionScheme::Semver)?;
+        let get_distance = |other: &str| -> Result<VersionDistance> {
+            Ok(version.distance(&Version::parse(other, &VersionScheme::Semver)?))
+        };
+        assert_eq!(get_distance("v1.2.4+build.5")?, VersionDistance::Exact);
+        assert_eq!(get_distance("1.2.3")?, VersionDistance::Patch);
+        assert_eq!(get_distance("1.2.4-beta.1")?, VersionDistance::Patch);
+        assert_eq!(get_distance("1.3.0")?, VersionDistance::Minor);
+        assert_eq!(get_distance("2.2.4")?, VersionDistance::Major);
+        Ok(())
+    }
+
+    #[test]
+    fn test_semver_unstable_minor_is_major() -> Result<()> {
+        let version = Version::parse("0.2.4", &VersionScheme::Semver)?;
+        let other = Version::parse("0.3.0", &VersionScheme::Semver)?;
+        assert_eq!(version.distance(&other), VersionDistance::Major);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pep440_parse() -> Result<()> {
+        let version = Version::parse("1!2.0rc1.post2+local.7", &VersionScheme::Pep440)?;
+        let expected = Version {
+            epoch: 1,
+            release: vec![2, 0],
+            label: Some("rc1.post2".to_string()),
+        };
+        assert_eq!(version, expected);
+
+        let version = Version::parse("2.0.0", &VersionScheme::Pep440)?;
+        let get_distance = |other: &str| -> Result<VersionDistance> {
+            Ok(version.distance(&Version::parse(other, &VersionScheme::Pep440)?))
+        };
+        assert_eq!(get_distance("2.0")?, VersionDistance::Exact);
+        assert_eq!(get_distance("2.0.post1")?, VersionDistance::Patch);
+        assert_eq!(get_distance("2.1.0.dev3")?, VersionDistance::Minor);
+        Ok(())
+    }
+}
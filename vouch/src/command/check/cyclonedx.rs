@@ -0,0 +1,329 @@
+//! Emit dependency review reports as a CycloneDX JSON document.
+//!
+//! Components are annotated with Vouch review data through `vouch:`-prefixed component
+//! properties. A CycloneDX SBOM given as input is annotated in place. Otherwise each checked
+//! dependency becomes a component of a new document.
+
+use anyhow::Result;
+
+use super::dependencies::DependenciesSpecReport;
+use super::report::DependencyReport;
+use super::sbom;
+use crate::common;
+
+static SPEC_VERSION: &str = "1.4";
+
+/// Returns a CycloneDX document with a component per dependency report.
+pub fn get(
+    spec_reports: &Vec<DependenciesSpecReport>,
+    config: &common::config::Config,
+) -> Result<serde_json::Value> {
+    let mut components = Vec::new();
+    let mut bom_refs = std::collections::BTreeSet::new();
+    for spec_report in spec_reports {
+        for dependency_report in &spec_report.dependency_reports {
+            let component =
+                get_component(&dependency_report, &spec_report.registry_host_name, &config);
+            // Dependencies may be listed in multiple specification files.
+            if bom_refs.insert(component["bom-ref"].to_string()) {
+                components.push(component);
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "serialNumber": format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+        "version": 1,
+        "metadata": {
+            "tools": [{
+                "vendor": "Vouch",
+                "name": "vouch",
+                "version": env!("CARGO_PKG_VERSION"),
+            }],
+        },
+        "components": components,
+    }))
+}
+
+/// Returns the given CycloneDX SBOM document with review data added to its components.
+///
+/// Components which were not checked are left unchanged. Earlier Vouch properties are replaced.
+pub fn annotate(
+    document: &serde_json::Value,
+    spec_reports: &Vec<DependenciesSpecReport>,
+    registries: &std::collections::BTreeMap<String, String>,
+    config: &common::config::Config,
+) -> Result<serde_json::Value> {
+    let mut document = document.clone();
+    if let Some(components) = document.get_mut("components") {
+        annotate_components(components, &spec_reports, &registries, &config)?;
+    }
+    Ok(document)
+}
+
+fn annotate_components(
+    components: &mut serde_json::Value,
+    spec_reports: &Vec<DependenciesSpecReport>,
+    registries: &std::collections::BTreeMap<String, String>,
+    config: &common::config::Config,
+) -> Result<()> {
+    let components = match components.as_array_mut() {
+        Some(components) => components,
+        None => return Ok(()),
+    };
+    for component in components {
+        if let Some(purl) = component["purl"].as_str() {
+            let purl: sbom::PackageUrl = purl.parse()?;
+            let dependency_report =
+                sbom::get_registry_host_name(&purl, &registries).and_then(|registry_host_name| {
+                    find_dependency_report(&spec_reports, &registry_host_name, &purl)
+                        .map(|dependency_report| (registry_host_name, dependency_report))
+                });
+            if let Some((registry_host_name, dependency_report)) = dependency_report {
+                let mut properties: Vec<_> = component["properties"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|property| {
+                        !property["name"]
+                            .as_str()
+                            .map_or(false, |name| name.starts_with("vouch:"))
+                    })
+                    .cloned()
+                    .collect();
+                properties.extend(get_properties(
+                    &dependency_report,
+                    &registry_host_name,
+                    &config,
+                ));
+                component["properties"] = serde_json::Value::Array(properties);
+            }
+        }
+        if let Some(components) = component.get_mut("components") {
+            annotate_components(components, &spec_reports, &registries, &config)?;
+        }
+    }
+    Ok(())
+}
+
+fn find_dependency_report<'a>(
+    spec_reports: &'a Vec<DependenciesSpecReport>,
+    registry_host_name: &str,
+    purl: &sbom::PackageUrl,
+) -> Option<&'a DependencyReport> {
+    let name = purl.registry_package_name();
+    spec_reports
+        .iter()
+        .filter(|spec_report| spec_report.registry_host_name == registry_host_name)
+        .flat_map(|spec_report| spec_report.dependency_reports.iter())
+        .find(|dependency_report| {
+            dependency_report.name == name && dependency_report.version == purl.version
+        })
+}
+
+fn get_component(
+    dependency_report: &DependencyReport,
+    registry_host_name: &str,
+    config: &common::config::Config,
+) -> serde_json::Value {
+    let purl = sbom::get_package_url(
+        &registry_host_name,
+        &dependency_report.name,
+        dependency_report.version.as_deref(),
+    );
+    let bom_ref = purl.clone().unwrap_or(format!(
+        "{registry}/{name}@{version}",
+        registry = registry_host_name,
+        name = dependency_report.name,
+        version = dependency_report.version.as_deref().unwrap_or("")
+    ));
+
+    let mut component = serde_json::json!({
+        "type": "library",
+        "bom-ref": bom_ref,
+        "name": dependency_report.name,
+        "properties": get_properties(&dependency_report, &registry_host_name, &config),
+    });
+    if let Some(version) = &dependency_report.version {
+        component["version"] = serde_json::json!(version);
+    }
+    if let Some(purl) = purl {
+        component["purl"] = serde_json::json!(purl);
+    }
+    component
+}
+
+/// Returns CycloneDX component properties which hold the review data of a dependency.
+fn get_properties(
+    dependency_report: &DependencyReport,
+    registry_host_name: &str,
+    config: &common::config::Config,
+) -> Vec<serde_json::Value> {
+    let mut properties = vec![
+        ("vouch:registry", registry_host_name.to_string()),
+        ("vouch:status", dependency_report.summary.to_string()),
+        (
+            "vouch:review-count",
+            dependency_report.review_count.unwrap_or(0).to_string(),
+        ),
+        (
+            "vouch:fail-comment-count",
+            dependency_report.count_fail_comments.to_string(),
+        ),
+        (
+            "vouch:warn-comment-count",
+            dependency_report.count_warn_comments.to_string(),
+        ),
+    ];
+    for peer in &dependency_report.peers {
+        let peer_url = if peer.is_root() {
+            match &config.core.root_git_url {
+                Some(url) => url.to_string(),
+                None => peer.alias.clone(),
+            }
+        } else {
+            peer.git_url.to_string()
+        };
+        properties.push(("vouch:peer", peer_url));
+    }
+    for criterion in &dependency_report.criteria {
+        properties.push(("vouch:criterion", criterion.clone()));
+    }
+    for criterion in &dependency_report.missing_criteria {
+        properties.push(("vouch:missing-criterion", criterion.clone()));
+    }
+    if let Some((version, distance)) = &dependency_report.nearest_reviewed_version {
+        properties.push(("vouch:nearest-reviewed-version", version.clone()));
+        properties.push((
+            "vouch:nearest-reviewed-version-distance",
+            distance.to_string(),
+        ));
+    }
+    if let Some(note) = &dependency_report.note {
+        if !note.is_empty() {
+            properties.push(("vouch:note", note.clone()));
+        }
+    }
+
+    properties
+        .into_iter()
+        .map(|(name, value)| serde_json::json!({"name": name, "value": value}))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review;
+
+    fn get_dependency_report() -> Result<DependencyReport> {
+        Ok(DependencyReport {
+            summary: review::Summary::Fail,
+            name: "numpy".to_string(),
+            version: Some("1.18.5".to_string()),
+            review_count: Some(1),
+            note: Some("fail (2)".to_string()),
+            nearest_reviewed_version: None,
+            count_fail_comments: 2,
+            count_warn_comments: 0,
+            peers: maplit::btreeset! {crate::peer::Peer {
+                alias: "peer".to_string(),
+                git_url: std::convert::TryFrom::try_from("https://github.com/user/reviews")?,
+                parent_id: Some(1),
+                ..Default::default()
+            }},
+            criteria: maplit::btreeset! {"safe-to-run".to_string()},
+            missing_criteria: maplit::btreeset! {"safe-to-deploy".to_string()},
+        })
+    }
+
+    #[test]
+    fn test_component_annotated_with_review_data() -> Result<()> {
+        let component = get_component(
+            &get_dependency_report()?,
+            "pypi.org",
+            &common::config::Config::default(),
+        );
+
+        assert_eq!(component["purl"], "pkg:pypi/numpy@1.18.5");
+        let properties = component["properties"].as_array().unwrap();
+        let get_property = |name: &str| {
+            properties
+                .iter()
+                .find(|property| property["name"] == name)
+                .map(|property| property["value"].clone())
+        };
+        assert_eq!(get_property("vouch:status"), Some("fail".into()));
+        assert_eq!(get_property("vouch:fail-comment-count"), Some("2".into()));
+        assert_eq!(get_property("vouch:criterion"), Some("safe-to-run".into()));
+        assert_eq!(
+            get_property("vouch:missing-criterion"),
+            Some("safe-to-deploy".into())
+        );
+        assert_eq!(
+            get_property("vouch:peer"),
+            Some("https://github.com/user/reviews".into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotate_keeps_input_sbom_components() -> Result<()> {
+        let document = serde_json::json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.3",
+            "metadata": {"component": {"name": "application"}},
+            "components": [{
+                "name": "application-lib",
+                "components": [{
+                    "name": "numpy",
+                    "purl": "pkg:pypi/numpy@1.18.5",
+                    "licenses": [{"license": {"id": "BSD-3-Clause"}}],
+                    "properties": [
+                        {"name": "build:scope", "value": "runtime"},
+                        {"name": "vouch:status", "value": "pass"}
+                    ]
+                }]
+            }, {
+                "name": "left-pad",
+                "purl": "pkg:npm/left-pad@1.3.0"
+            }]
+        });
+        let spec_reports = vec![DependenciesSpecReport {
+            extension_name: "py".to_string(),
+            path: std::path::PathBuf::from("/sbom.json"),
+            registry_host_name: "pypi.org".to_string(),
+            dependency_reports: vec![get_dependency_report()?],
+        }];
+        let registries = maplit::btreemap! {
+            "pypi.org".to_string() => "py".to_string(),
+        };
+
+        let result = annotate(
+            &document,
+            &spec_reports,
+            &registries,
+            &common::config::Config::default(),
+        )?;
+        assert_eq!(result["specVersion"], "1.3");
+        assert_eq!(result["metadata"], document["metadata"]);
+        assert_eq!(result["components"][1], document["components"][1]);
+
+        let component = &result["components"][0]["components"][0];
+        assert_eq!(
+            component["licenses"],
+            document["components"][0]["components"][0]["licenses"]
+        );
+        let properties = component["properties"].as_array().unwrap();
+        assert_eq!(properties[0]["name"], "build:scope");
+        let statuses: Vec<_> = properties
+            .iter()
+            .filter(|property| property["name"] == "vouch:status")
+            .map(|property| property["value"].clone())
+            .collect();
+        assert_eq!(statuses, vec![serde_json::json!("fail")]);
+        Ok(())
+    }
+}
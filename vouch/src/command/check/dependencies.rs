@@ -1,22 +1,25 @@
-use anyhow::Result;
+use anyhow::{format_err, Context, Result};
 
 use crate::common;
 use crate::common::StoreTransaction;
 use crate::extension;
+use crate::review;
 
 use super::report;
+use super::sbom;
 use super::table;
 
 pub fn report(
     extension_names: &std::collections::BTreeSet<String>,
+    policy: &report::Policy,
     config: &common::config::Config,
     tx: &StoreTransaction,
-) -> Result<()> {
+) -> Result<Vec<DependenciesSpecReport>> {
     let extensions = extension::manage::get_enabled(&extension_names, &config)?;
+    let mut spec_reports = Vec::new();
     let working_directory = std::env::current_dir()?;
     log::debug!("Current working directory: {}", working_directory.display());
 
-    let mut dependencies_found = false;
     let all_dependencies_specs =
         extension::identify_local_dependencies(&extensions, &working_directory)?;
     for (extension, extension_dependencies_specs) in
@@ -30,21 +33,152 @@ pub fn report(
         let extension_dependencies_specs = match extension_dependencies_specs {
             Ok(d) => d,
             Err(error) => {
-                log::error!("Extension error: {}", error);
+                eprintln!(
+                    "Extension {name} failed to identify dependencies: {error}",
+                    name = extension.name(),
+                    error = error
+                );
                 continue;
             }
         };
         for dependencies_spec in extension_dependencies_specs {
-            dependencies_found |= !dependencies_spec.dependencies.is_empty();
-            report_dependencies_spec(&dependencies_spec, &extension, &tx)?;
+            spec_reports.extend(report_dependencies_spec(
+                &dependencies_spec,
+                &extension,
+                &policy,
+                &tx,
+            )?);
+        }
+    }
+    Ok(spec_reports)
+}
+
+/// Report on dependencies specified within the given files and, optionally, a file read from stdin.
+pub fn report_files(
+    file_paths: &Vec<std::path::PathBuf>,
+    read_stdin: bool,
+    extension_names: &std::collections::BTreeSet<String>,
+    policy: &report::Policy,
+    config: &common::config::Config,
+    tx: &StoreTransaction,
+) -> Result<Vec<DependenciesSpecReport>> {
+    let extensions = extension::manage::get_enabled(&extension_names, &config)?;
+    let mut spec_reports = Vec::new();
+
+    for file_path in file_paths {
+        if !file_path.is_file() {
+            return Err(format_err!(
+                "Dependencies file not found: {}",
+                file_path.display()
+            ));
+        }
+        let file_path = file_path.canonicalize()?;
+        let file_name = file_path.file_name().and_then(|name| name.to_str());
+        let (extension, dependencies_spec) =
+            extension::identify_file_dependencies(&extensions, &file_path, file_name)?;
+        spec_reports.extend(report_dependencies_spec(
+            &dependencies_spec,
+            &extension,
+            &policy,
+            &tx,
+        )?);
+    }
+
+    if read_stdin {
+        let tmp_dir = tempdir::TempDir::new("vouch_check_stdin")?;
+        let file_path = tmp_dir.path().join("stdin");
+        let mut file = std::fs::File::create(&file_path)?;
+        std::io::copy(&mut std::io::stdin(), &mut file)?;
+
+        let (extension, mut dependencies_spec) =
+            extension::identify_file_dependencies(&extensions, &file_path, None)?;
+        dependencies_spec.path = std::path::PathBuf::from("<stdin>");
+        spec_reports.extend(report_dependencies_spec(
+            &dependencies_spec,
+            &extension,
+            &policy,
+            &tx,
+        )?);
+    }
+    Ok(spec_reports)
+}
+
+/// Report on dependencies listed within SBOM documents.
+///
+/// Components are assigned to extensions via the registries of `config.extensions.registries`.
+pub fn report_sboms(
+    sbom_paths: &Vec<std::path::PathBuf>,
+    extension_names: &std::collections::BTreeSet<String>,
+    policy: &report::Policy,
+    config: &common::config::Config,
+    tx: &StoreTransaction,
+) -> Result<Vec<DependenciesSpecReport>> {
+    let extensions = extension::manage::get_enabled(&extension_names, &config)?;
+    let mut spec_reports = Vec::new();
+
+    for sbom_path in sbom_paths {
+        let sbom_path = sbom_path
+            .canonicalize()
+            .context(format!("SBOM file not found: {}", sbom_path.display()))?;
+        let purls = sbom::read(&sbom_path)?;
+        let (dependencies_specs, unsupported_types) =
+            sbom::get_dependencies_specs(&sbom_path, &purls, &config.extensions.registries);
+
+        for dependencies_spec in dependencies_specs {
+            let extension_name =
+                &config.extensions.registries[&dependencies_spec.registry_host_name];
+            let extension = match extensions
+                .iter()
+                .find(|extension| &extension.name() == extension_name)
+            {
+                Some(extension) => extension,
+                None => {
+                    log::warn!(
+                        "Skipping SBOM components from registry {registry}. \
+                        Extension not enabled: {name}",
+                        registry = dependencies_spec.registry_host_name,
+                        name = extension_name
+                    );
+                    continue;
+                }
+            };
+            spec_reports.extend(report_dependencies_spec(
+                &dependencies_spec,
+                &extension,
+                &policy,
+                &tx,
+            )?);
+        }
+
+        if !unsupported_types.is_empty() {
+            log::warn!(
+                "Skipped SBOM components with unsupported package types: {}",
+                unsupported_types.into_iter().collect::<Vec<_>>().join(", ")
+            );
         }
     }
+    Ok(spec_reports)
+}
 
-    if !dependencies_found {
+/// Review reports for the dependencies of a single dependencies specification.
+#[derive(Debug, Clone)]
+pub struct DependenciesSpecReport {
+    pub extension_name: String,
+    pub path: std::path::PathBuf,
+    pub registry_host_name: String,
+    pub dependency_reports: Vec<report::DependencyReport>,
+}
+
+/// Print a table per dependencies specification.
+pub fn print_tables(spec_reports: &Vec<DependenciesSpecReport>) -> Result<()> {
+    for spec_report in spec_reports {
+        let table = table::get(&spec_report.dependency_reports)?;
         println!(
-            "No dependency specification files found in \
-            working directory or parent directories."
-        )
+            "\n\nExtension: {name}\n{path}",
+            name = spec_report.extension_name,
+            path = spec_report.path.display(),
+        );
+        table.printstd();
     }
     Ok(())
 }
@@ -52,37 +186,75 @@ pub fn report(
 fn report_dependencies_spec(
     dependencies_spec: &vouch_lib::extension::DependenciesSpec,
     extension: &Box<dyn vouch_lib::extension::Extension>,
+    policy: &report::Policy,
     tx: &StoreTransaction,
-) -> Result<()> {
+) -> Result<Option<DependenciesSpecReport>> {
     log::info!(
         "Generating report for dependencies specification file: {}",
         dependencies_spec.path.display()
     );
     let dependencies = &dependencies_spec.dependencies;
 
+    let known_reviews = get_known_reviews(
+        &dependencies,
+        &dependencies_spec.registry_host_name,
+        extension.version_scheme(),
+        &policy,
+        &tx,
+    )?;
+
     let dependency_reports: Result<Vec<report::DependencyReport>> = dependencies
         .into_iter()
         .map(|dependency| -> Result<report::DependencyReport> {
-            Ok(report::get_dependency_report(
-                &dependency,
-                &dependencies_spec.registry_host_name,
-                &tx,
-            )?)
+            Ok(report::get_dependency_report(&dependency, &known_reviews)?)
         })
         .collect();
     let dependency_reports = dependency_reports?;
 
     log::info!("Number of dependencies found: {}", dependency_reports.len());
     if dependency_reports.is_empty() {
-        return Ok(());
+        return Ok(None);
     }
 
-    let table = table::get(&dependency_reports)?;
-    println!(
-        "\n\nExtension: {name}\n{path}",
-        name = extension.name(),
-        path = dependencies_spec.path.display(),
-    );
-    table.printstd();
-    Ok(())
+    Ok(Some(DependenciesSpecReport {
+        extension_name: extension.name(),
+        path: dependencies_spec.path.clone(),
+        registry_host_name: dependencies_spec.registry_host_name.clone(),
+        dependency_reports,
+    }))
+}
+
+/// Find reviews for all dependencies in batches.
+///
+/// Reviews of other package versions are only queried for dependencies without reviews.
+fn get_known_reviews(
+    dependencies: &Vec<vouch_lib::extension::Dependency>,
+    registry_host_name: &str,
+    version_scheme: vouch_lib::extension::VersionScheme,
+    policy: &report::Policy,
+    tx: &StoreTransaction,
+) -> Result<report::KnownReviews> {
+    let packages = dependencies
+        .iter()
+        .filter_map(|dependency| match &dependency.version {
+            Ok(version) => Some((dependency.name.as_str(), version.as_str())),
+            Err(_) => None,
+        })
+        .collect();
+    let by_package = review::index::get_by_packages(&packages, &registry_host_name, &tx)?;
+
+    let unreviewed_package_names = by_package
+        .iter()
+        .filter(|(_package, reviews)| reviews.is_empty())
+        .map(|((name, _version), _reviews)| name.as_str())
+        .collect();
+    let by_package_name =
+        review::index::get_by_package_names(&unreviewed_package_names, &registry_host_name, &tx)?;
+
+    Ok(report::KnownReviews {
+        by_package,
+        by_package_name,
+        version_scheme,
+        policy: policy.clone(),
+    })
 }
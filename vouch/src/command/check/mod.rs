@@ -1,15 +1,38 @@
-use anyhow::Result;
+use anyhow::{format_err, Result};
 use structopt::{self, StructOpt};
 
 use crate::common;
 use crate::extension;
 use crate::store;
 
+mod cyclonedx;
 mod dependencies;
 mod report;
+mod sbom;
 mod specific;
 mod table;
 
+/// Dependencies report output format.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Emit {
+    Table,
+    CycloneDx,
+}
+
+impl std::str::FromStr for Emit {
+    type Err = anyhow::Error;
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "table" => Ok(Emit::Table),
+            "cyclonedx" => Ok(Emit::CycloneDx),
+            _ => Err(anyhow::format_err!(
+                "Expected value: `table` or `cyclonedx`. Found: {}",
+                value
+            )),
+        }
+    }
+}
+
 #[derive(Debug, StructOpt, Clone)]
 #[structopt(
     name = "no_version",
@@ -29,31 +52,220 @@ pub struct Arguments {
     /// Example values: py, js, rs
     #[structopt(long = "extension", short = "e", name = "name")]
     pub extension_names: Option<Vec<String>>,
+
+    /// Check dependencies specification file instead of searching the working directory.
+    /// Can be given multiple times.
+    /// Example: --file subproject/poetry.lock
+    #[structopt(
+        long = "file",
+        short = "f",
+        name = "path",
+        parse(from_os_str),
+        number_of_values = 1,
+        conflicts_with = "package-name"
+    )]
+    pub file_paths: Vec<std::path::PathBuf>,
+
+    /// Check dependencies listed in a CycloneDX or SPDX JSON SBOM document.
+    /// Can be given multiple times.
+    #[structopt(
+        long = "sbom",
+        name = "sbom-path",
+        parse(from_os_str),
+        number_of_values = 1,
+        conflicts_with = "package-name"
+    )]
+    pub sbom_paths: Vec<std::path::PathBuf>,
+
+    /// Read dependencies specification file from stdin. Requires --format.
+    #[structopt(long = "stdin", requires = "format", conflicts_with = "package-name")]
+    pub stdin: bool,
+
+    /// Extension which parses the given dependencies specification files.
+    /// Example values: py, js
+    #[structopt(long = "format", name = "format", conflicts_with = "name")]
+    pub format: Option<String>,
+
+    /// Accept reviews of other package versions up to this distance when checking dependencies.
+    /// Package reports list reviews of all versions instead.
+    /// Overrides config setting `check.accept-version-distance`.
+    /// Possible values: exact, patch, minor, major
+    #[structopt(
+        long = "accept-version-distance",
+        name = "distance",
+        conflicts_with = "package-name"
+    )]
+    pub accept_version_distance: Option<vouch_lib::extension::VersionDistance>,
+
+    /// Require that at least one counted review certifies the given review criterion.
+    /// Dependencies without such a review are reported as warnings.
+    /// Can be given multiple times. Adds to config setting `check.require-criteria`.
+    /// Example: --require safe-to-deploy
+    #[structopt(long = "require", name = "criterion", number_of_values = 1)]
+    pub require_criteria: Vec<String>,
+
+    /// Only count reviews with at least this reviewer confidence.
+    /// Overrides config setting `check.min-confidence`.
+    /// Possible values: low, medium, high
+    #[structopt(long = "min-confidence", name = "confidence")]
+    pub min_confidence: Option<crate::review::Confidence>,
+
+    /// Dependencies report output format. Format cyclonedx writes a CycloneDX JSON document
+    /// with review data as component properties. A CycloneDX document given with --sbom is
+    /// annotated in place.
+    /// Possible values: table (default), cyclonedx
+    #[structopt(long = "emit", name = "emit-format", conflicts_with = "package-name")]
+    pub emit: Option<Emit>,
 }
 
 pub fn run_command(args: &Arguments) -> Result<()> {
     let mut config = common::config::Config::load()?;
     extension::manage::update_config(&mut config)?;
     let config = config;
-    let extension_names =
-        extension::manage::handle_extension_names_arg(&args.extension_names, &config)?;
+    let extension_names = match &args.format {
+        Some(format) => {
+            extension::manage::handle_extension_names_arg(&Some(vec![format.clone()]), &config)?
+        }
+        None => extension::manage::handle_extension_names_arg(&args.extension_names, &config)?,
+    };
+    let mut required_criteria = config.check.require_criteria.clone();
+    required_criteria.extend(args.require_criteria.iter().cloned());
+    config.review_criteria.check_known(&required_criteria)?;
 
     let mut store = store::Store::from_root()?;
     let tx = store.get_transaction()?;
 
+    let policy = report::Policy {
+        accept_version_distance: args
+            .accept_version_distance
+            .unwrap_or(config.check.accept_version_distance),
+        required_criteria,
+        min_confidence: args.min_confidence.or(config.check.min_confidence),
+        quarantined_peers: get_quarantined_peers(&config, &tx)?,
+    };
+
     match &args.package_name {
         Some(package_name) => {
             specific::report(
                 &package_name,
                 &args.package_version,
                 &extension_names,
+                &policy,
                 &config,
                 &tx,
             )?;
         }
         None => {
-            dependencies::report(&extension_names, &config, &tx)?;
+            let emit = args.emit.unwrap_or(Emit::Table);
+            let is_sbom_only = !args.stdin && args.file_paths.is_empty();
+            if emit == Emit::CycloneDx && !args.sbom_paths.is_empty() {
+                if args.sbom_paths.len() > 1 || !is_sbom_only {
+                    return Err(format_err!(
+                        "Option --emit cyclonedx annotates a single --sbom document. \
+                        It can not be combined with other dependency inputs."
+                    ));
+                }
+            }
+
+            let mut spec_reports = Vec::new();
+            let has_file_inputs =
+                args.stdin || !args.file_paths.is_empty() || !args.sbom_paths.is_empty();
+            if !has_file_inputs {
+                spec_reports.extend(dependencies::report(
+                    &extension_names,
+                    &policy,
+                    &config,
+                    &tx,
+                )?);
+            }
+            if args.stdin || !args.file_paths.is_empty() {
+                spec_reports.extend(dependencies::report_files(
+                    &args.file_paths,
+                    args.stdin,
+                    &extension_names,
+                    &policy,
+                    &config,
+                    &tx,
+                )?);
+            }
+            if !args.sbom_paths.is_empty() {
+                spec_reports.extend(dependencies::report_sboms(
+                    &args.sbom_paths,
+                    &extension_names,
+                    &policy,
+                    &config,
+                    &tx,
+                )?);
+            }
+
+            match emit {
+                Emit::Table => {
+                    if spec_reports.is_empty() && !has_file_inputs {
+                        println!(
+                            "No dependency specification files found in \
+                            working directory or parent directories."
+                        )
+                    }
+                    dependencies::print_tables(&spec_reports)?;
+                }
+                Emit::CycloneDx => {
+                    let sbom_document = match args.sbom_paths.first() {
+                        Some(sbom_path) => Some(sbom::read_document(&sbom_path)?),
+                        None => None,
+                    };
+                    let document = match sbom_document {
+                        // SPDX documents can not hold CycloneDX properties.
+                        Some(sbom_document) if sbom::is_cyclonedx(&sbom_document) => {
+                            cyclonedx::annotate(
+                                &sbom_document,
+                                &spec_reports,
+                                &config.extensions.registries,
+                                &config,
+                            )?
+                        }
+                        _ => cyclonedx::get(&spec_reports, &config)?,
+                    };
+                    println!("{}", serde_json::to_string_pretty(&document)?);
+                }
+            }
         }
     }
     Ok(())
 }
+
+/// Returns the git URLs of quarantined peers. Empty unless quarantine is enabled.
+fn get_quarantined_peers(
+    config: &common::config::Config,
+    tx: &common::StoreTransaction,
+) -> Result<std::collections::BTreeSet<String>> {
+    if !config.quarantine.enabled {
+        return Ok(std::collections::BTreeSet::new());
+    }
+    Ok(
+        crate::peer::index::get_quarantined(&config.quarantine.approved_peers, &tx)?
+            .into_iter()
+            .map(|peer| peer.git_url.to_string())
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_arguments() -> Result<()> {
+        let args = Arguments::from_iter_safe(&["check", "foo", "1.0"])?;
+        assert_eq!(args.package_name, Some("foo".to_string()));
+        assert_eq!(args.package_version, Some("1.0".to_string()));
+        assert_eq!(args.emit, None);
+
+        let args = Arguments::from_iter_safe(&["check", "--emit", "cyclonedx"])?;
+        assert_eq!(args.emit, Some(Emit::CycloneDx));
+
+        let result =
+            Arguments::from_iter_safe(&["check", "foo", "--accept-version-distance", "minor"]);
+        assert!(result.is_err());
+        Ok(())
+    }
+}
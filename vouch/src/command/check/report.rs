@@ -1,22 +1,95 @@
 use anyhow::Result;
 
-use crate::common::StoreTransaction;
+use crate::common::ContentHash;
 use crate::review;
+use vouch_lib::extension::{Version, VersionDistance, VersionScheme};
 
 #[derive(Debug, Clone, Eq, Ord, PartialEq, PartialOrd)]
 pub struct DependencyReport {
     pub summary: review::Summary,
     pub name: String,
     pub version: Option<String>,
+
+    /// Number of distinct reviewers.
     pub review_count: Option<usize>,
     pub note: Option<String>,
+
+    /// Nearest reviewed version and its distance from the dependency version. Set when the
+    /// dependency version itself has not been reviewed.
+    pub nearest_reviewed_version: Option<(String, VersionDistance)>,
+
+    pub count_fail_comments: i32,
+    pub count_warn_comments: i32,
+
+    /// Peers which authored the reviews counted for this dependency.
+    pub peers: std::collections::BTreeSet<crate::peer::Peer>,
+
+    /// Review criteria certified by at least one counted review.
+    pub criteria: std::collections::BTreeSet<String>,
+
+    /// Required review criteria which no counted review certifies.
+    pub missing_criteria: std::collections::BTreeSet<String>,
+}
+
+/// Conditions under which reviews count towards a dependency passing.
+#[derive(Debug, Default, Clone)]
+pub struct Policy {
+    /// Reviews of other versions up to this distance count as reviews of the dependency.
+    pub accept_version_distance: VersionDistance,
+
+    /// Review criteria which at least one counted review must certify.
+    pub required_criteria: std::collections::BTreeSet<String>,
+
+    /// Reviews with a lower or unknown reviewer confidence are not counted.
+    pub min_confidence: Option<review::Confidence>,
+
+    /// Git URLs of quarantined peers. Their reviews are not counted.
+    pub quarantined_peers: std::collections::BTreeSet<String>,
+}
+
+impl Policy {
+    /// Returns true if the review counts towards a dependency report.
+    fn is_counted(&self, review: &review::Review) -> bool {
+        if self
+            .quarantined_peers
+            .contains(review.peer.git_url.as_str())
+        {
+            return false;
+        }
+        self.is_confidence_accepted(&review)
+    }
+
+    /// Returns true if the review meets the minimum reviewer confidence.
+    pub fn is_confidence_accepted(&self, review: &review::Review) -> bool {
+        match self.min_confidence {
+            Some(min_confidence) => review
+                .review_confidence
+                .map_or(false, |confidence| confidence >= min_confidence),
+            None => true,
+        }
+    }
+}
+
+/// Known reviews for the dependencies of a single dependencies specification file.
+#[derive(Debug, Default, Clone)]
+pub struct KnownReviews {
+    /// Reviews per (package name, package version) as returned by
+    /// `review::index::get_by_packages`.
+    pub by_package: std::collections::BTreeMap<(String, String), Vec<review::Review>>,
+
+    /// Reviews of any version per package name as returned by
+    /// `review::index::get_by_package_names`.
+    pub by_package_name: std::collections::BTreeMap<String, Vec<review::Review>>,
+
+    pub version_scheme: VersionScheme,
+
+    pub policy: Policy,
 }
 
 /// Given a local project dependency, create a corresponding review report from known reviews.
 pub fn get_dependency_report(
     dependency: &vouch_lib::extension::Dependency,
-    registry_host_name: &str,
-    tx: &StoreTransaction,
+    known_reviews: &KnownReviews,
 ) -> Result<DependencyReport> {
     let package_version = match &dependency.version {
         Ok(version) => version.clone(),
@@ -27,19 +100,36 @@ pub fn get_dependency_report(
                 version: None,
                 review_count: None,
                 note: Some(error.message()),
+                nearest_reviewed_version: None,
+                count_fail_comments: 0,
+                count_warn_comments: 0,
+                peers: std::collections::BTreeSet::new(),
+                criteria: std::collections::BTreeSet::new(),
+                missing_criteria: known_reviews.policy.required_criteria.clone(),
             });
         }
     };
 
-    let reviews = review::index::get(
-        &review::index::Fields {
-            package_name: Some(&dependency.name),
-            package_version: Some(&package_version),
-            registry_host_names: Some(maplit::btreeset! {registry_host_name}),
-            ..Default::default()
-        },
-        &tx,
-    )?;
+    let mut reviews = known_reviews
+        .by_package
+        .get(&(dependency.name.clone(), package_version.clone()))
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|review| known_reviews.policy.is_counted(&review))
+        .collect::<Vec<_>>();
+
+    let mut nearest_reviewed_version = None;
+    if reviews.is_empty() {
+        if let Some((version, distance, nearest_reviews)) =
+            get_nearest_version_reviews(&dependency.name, &package_version, &known_reviews)
+        {
+            if distance <= known_reviews.policy.accept_version_distance {
+                reviews = nearest_reviews;
+            }
+            nearest_reviewed_version = Some((version, distance));
+        }
+    }
 
     if reviews.is_empty() {
         // Report no reviews found for dependency.
@@ -48,28 +138,158 @@ pub fn get_dependency_report(
             name: dependency.name.clone(),
             version: Some(package_version.clone()),
             review_count: Some(0),
-            note: None,
+            note: get_nearest_version_note(&nearest_reviewed_version),
+            nearest_reviewed_version,
+            count_fail_comments: 0,
+            count_warn_comments: 0,
+            peers: std::collections::BTreeSet::new(),
+            criteria: std::collections::BTreeSet::new(),
+            missing_criteria: known_reviews.policy.required_criteria.clone(),
         });
     }
 
+    let reviews = get_distinct_reviews(reviews);
+    let reviewer_count = reviews
+        .iter()
+        .map(|review| review.peer.git_url.to_string())
+        .collect::<std::collections::BTreeSet<_>>()
+        .len();
+
     let stats = get_dependency_stats(&reviews)?;
-    let status = get_dependency_status(&stats)?;
-    let note = get_dependency_note(&stats)?;
+    let criteria = reviews
+        .iter()
+        .flat_map(|review| review.criteria.iter().cloned())
+        .collect::<std::collections::BTreeSet<_>>();
+    let missing_criteria = known_reviews
+        .policy
+        .required_criteria
+        .difference(&criteria)
+        .cloned()
+        .collect::<std::collections::BTreeSet<_>>();
+
+    let mut status = get_dependency_status(&stats)?;
+    if status == review::Summary::Pass && !missing_criteria.is_empty() {
+        status = review::Summary::Warn;
+    }
+    let mut note = get_dependency_note(&stats)?;
+    if !missing_criteria.is_empty() {
+        let criteria_note = format!(
+            "missing criteria: {}",
+            missing_criteria
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        note = if note.is_empty() {
+            criteria_note
+        } else {
+            format!("{}; {}", note, criteria_note)
+        };
+    }
+    let note = match get_nearest_version_note(&nearest_reviewed_version) {
+        Some(version_note) if note.is_empty() => version_note,
+        Some(version_note) => format!("{}; {}", version_note, note),
+        None => note,
+    };
 
     Ok(DependencyReport {
         summary: status,
         name: dependency.name.clone(),
         version: Some(package_version.clone()),
-        review_count: Some(reviews.len()),
+        review_count: Some(reviewer_count),
         note: Some(note),
+        nearest_reviewed_version,
+        count_fail_comments: stats.count_fail_comments,
+        count_warn_comments: stats.count_warn_comments,
+        peers: reviews.iter().map(|review| review.peer.clone()).collect(),
+        criteria,
+        missing_criteria,
     })
 }
 
+/// Returns reviews with duplicate copies removed.
+///
+/// Copies of a review share the originating peer git URL and content hash.
+fn get_distinct_reviews(reviews: Vec<review::Review>) -> Vec<review::Review> {
+    let mut keys = std::collections::HashSet::new();
+    reviews
+        .into_iter()
+        .filter(|review| keys.insert((review.peer.git_url.to_string(), review.content_hash())))
+        .collect()
+}
+
+/// Returns reviews of the package version nearest to the given version, with that version and its
+/// distance. The latest version is preferred between versions at equal distance.
+///
+/// Versions which fail to parse are ignored.
+fn get_nearest_version_reviews(
+    package_name: &str,
+    package_version: &str,
+    known_reviews: &KnownReviews,
+) -> Option<(String, VersionDistance, Vec<review::Review>)> {
+    let scheme = &known_reviews.version_scheme;
+    let target_version = Version::parse(&package_version, &scheme).ok()?;
+
+    let mut nearest: Option<(VersionDistance, Version, Vec<review::Review>)> = None;
+    for review in known_reviews.by_package_name.get(package_name)? {
+        if !known_reviews.policy.is_counted(&review) {
+            continue;
+        }
+        let version = match Version::parse(&review.package.version, &scheme) {
+            Ok(version) => version,
+            Err(_) => {
+                log::debug!(
+                    "Ignoring review of unparsable package version: {} {}",
+                    review.package.name,
+                    review.package.version
+                );
+                continue;
+            }
+        };
+        let distance = target_version.distance(&version);
+
+        match &mut nearest {
+            Some((nearest_distance, nearest_version, nearest_reviews))
+                if *nearest_distance == distance && *nearest_version == version =>
+            {
+                nearest_reviews.push(review.clone());
+            }
+            Some((nearest_distance, nearest_version, _))
+                if (distance, std::cmp::Reverse(&version))
+                    >= (*nearest_distance, std::cmp::Reverse(nearest_version)) => {}
+            _ => {
+                nearest = Some((distance, version, vec![review.clone()]));
+            }
+        }
+    }
+
+    let (distance, _version, reviews) = nearest?;
+    let version = reviews.first()?.package.version.clone();
+    Some((version, distance, reviews))
+}
+
+fn get_nearest_version_note(
+    nearest_reviewed_version: &Option<(String, VersionDistance)>,
+) -> Option<String> {
+    nearest_reviewed_version
+        .as_ref()
+        .map(|(version, distance)| format!("nearest reviewed: {} ({})", version, distance))
+}
+
 #[derive(Debug, Default, Clone)]
 struct DependencyStats {
     pub total_review_count: usize,
     pub count_fail_comments: i32,
     pub count_warn_comments: i32,
+
+    /// Reviews by overall summary, whether given as a verdict or inferred from comments.
+    pub count_fail_reviews: i32,
+    pub count_warn_reviews: i32,
+
+    /// Reviews with an explicit fail or warn verdict.
+    pub count_fail_verdicts: i32,
+    pub count_warn_verdicts: i32,
 }
 
 fn get_dependency_stats(reviews: &Vec<review::Review>) -> Result<DependencyStats> {
@@ -80,15 +300,26 @@ fn get_dependency_stats(reviews: &Vec<review::Review>) -> Result<DependencyStats
         let review_analysis = review::analyse(&review)?;
         stats.count_fail_comments += review_analysis.count_fail_comments;
         stats.count_warn_comments += review_analysis.count_warn_comments;
+
+        match review_analysis.summary {
+            review::Summary::Fail => stats.count_fail_reviews += 1,
+            review::Summary::Warn => stats.count_warn_reviews += 1,
+            review::Summary::Pass => {}
+        }
+        match review.package_security {
+            Some(review::Summary::Fail) => stats.count_fail_verdicts += 1,
+            Some(review::Summary::Warn) => stats.count_warn_verdicts += 1,
+            _ => {}
+        }
     }
     Ok(stats)
 }
 
 fn get_dependency_status(stats: &DependencyStats) -> Result<review::Summary> {
-    if stats.count_fail_comments > 0 {
+    if stats.count_fail_reviews > 0 {
         return Ok(review::Summary::Fail);
     }
-    if stats.total_review_count == 0 || stats.count_warn_comments > 0 {
+    if stats.total_review_count == 0 || stats.count_warn_reviews > 0 {
         return Ok(review::Summary::Warn);
     }
     Ok(review::Summary::Pass)
@@ -96,6 +327,13 @@ fn get_dependency_status(stats: &DependencyStats) -> Result<review::Summary> {
 
 fn get_dependency_note(stats: &DependencyStats) -> Result<String> {
     let mut note_parts = Vec::<_>::new();
+    if stats.count_fail_verdicts > 0 {
+        note_parts.push(format!("verdict fail ({})", stats.count_fail_verdicts));
+    }
+    if stats.count_warn_verdicts > 0 {
+        note_parts.push(format!("verdict warn ({})", stats.count_warn_verdicts));
+    }
+
     if stats.count_fail_comments > 0 {
         note_parts.push(format!("fail ({})", stats.count_fail_comments));
     }
@@ -106,3 +344,120 @@ fn get_dependency_note(stats: &DependencyStats) -> Result<String> {
 
     Ok(note_parts.join("; "))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_review(
+        package_security: Option<review::Summary>,
+        review_confidence: Option<review::Confidence>,
+    ) -> review::Review {
+        review::Review {
+            id: 1,
+            peer: crate::peer::Peer::default(),
+            package: crate::package::Package {
+                id: 1,
+                name: "numpy".to_string(),
+                version: "1.18.5".to_string(),
+                registries: std::collections::BTreeSet::new(),
+                artifact_hash: "artifact_hash".to_string(),
+            },
+            comments: std::collections::BTreeSet::new(),
+            criteria: std::collections::BTreeSet::new(),
+            package_security,
+            review_confidence,
+        }
+    }
+
+    fn get_known_reviews(reviews: Vec<review::Review>, policy: Policy) -> KnownReviews {
+        KnownReviews {
+            by_package: maplit::btreemap! {
+                ("numpy".to_string(), "1.18.5".to_string()) => reviews,
+            },
+            policy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_verdict_determines_summary() -> Result<()> {
+        let dependency = vouch_lib::extension::Dependency {
+            name: "numpy".to_string(),
+            version: Ok("1.18.5".to_string()),
+        };
+        let known_reviews = get_known_reviews(
+            vec![get_review(
+                Some(review::Summary::Fail),
+                Some(review::Confidence::High),
+            )],
+            Policy::default(),
+        );
+
+        let result = get_dependency_report(&dependency, &known_reviews)?;
+        assert_eq!(result.summary, review::Summary::Fail);
+        assert_eq!(result.note, Some("verdict fail (1)".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_review_copies_counted_once() -> Result<()> {
+        let dependency = vouch_lib::extension::Dependency {
+            name: "numpy".to_string(),
+            version: Ok("1.18.5".to_string()),
+        };
+        let review = get_review(Some(review::Summary::Fail), None);
+        let mut review_copy = review.clone();
+        review_copy.id = 2;
+        let known_reviews = get_known_reviews(vec![review, review_copy], Policy::default());
+
+        let result = get_dependency_report(&dependency, &known_reviews)?;
+        assert_eq!(result.review_count, Some(1));
+        assert_eq!(result.note, Some("verdict fail (1)".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_low_confidence_review_not_counted() -> Result<()> {
+        let dependency = vouch_lib::extension::Dependency {
+            name: "numpy".to_string(),
+            version: Ok("1.18.5".to_string()),
+        };
+        let known_reviews = get_known_reviews(
+            vec![get_review(
+                Some(review::Summary::Pass),
+                Some(review::Confidence::Low),
+            )],
+            Policy {
+                min_confidence: Some(review::Confidence::Medium),
+                ..Default::default()
+            },
+        );
+
+        let result = get_dependency_report(&dependency, &known_reviews)?;
+        assert_eq!(result.summary, review::Summary::Warn);
+        assert_eq!(result.review_count, Some(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_quarantined_peer_review_not_counted() -> Result<()> {
+        let dependency = vouch_lib::extension::Dependency {
+            name: "numpy".to_string(),
+            version: Ok("1.18.5".to_string()),
+        };
+        let review = get_review(Some(review::Summary::Pass), None);
+        let known_reviews = get_known_reviews(
+            vec![review.clone()],
+            Policy {
+                quarantined_peers: maplit::btreeset! {review.peer.git_url.to_string()},
+                ..Default::default()
+            },
+        );
+
+        let result = get_dependency_report(&dependency, &known_reviews)?;
+        assert_eq!(result.summary, review::Summary::Warn);
+        assert_eq!(result.review_count, Some(0));
+        Ok(())
+    }
+}
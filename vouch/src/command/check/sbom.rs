@@ -0,0 +1,353 @@
+//! Read dependencies from software bill of materials (SBOM) documents.
+//!
+//! Supports CycloneDX and SPDX documents in JSON format. Components are identified by package URL
+//! (purl) and mapped to Vouch registries.
+
+use anyhow::{format_err, Context, Result};
+
+/// Known registry host names for package URL types. The first host name which is handled by an
+/// installed extension is selected.
+static PURL_TYPE_REGISTRY_HOST_NAMES: &[(&str, &[&str])] = &[
+    ("cargo", &["crates.io"]),
+    ("composer", &["packagist.org"]),
+    ("gem", &["rubygems.org"]),
+    ("golang", &["pkg.go.dev", "proxy.golang.org"]),
+    ("hex", &["hex.pm"]),
+    ("maven", &["repo1.maven.org", "search.maven.org"]),
+    ("npm", &["npmjs.com", "www.npmjs.com", "registry.npmjs.org"]),
+    ("nuget", &["nuget.org", "www.nuget.org"]),
+    ("pypi", &["pypi.org"]),
+];
+
+/// A parsed package URL.
+///
+/// See: https://github.com/package-url/purl-spec
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct PackageUrl {
+    pub package_type: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub version: Option<String>,
+    pub repository_url: Option<String>,
+}
+
+impl PackageUrl {
+    /// Returns the package name as known to the package registry.
+    pub fn registry_package_name(&self) -> String {
+        match &self.namespace {
+            Some(namespace) if self.package_type == "maven" => {
+                format!("{}:{}", namespace, self.name)
+            }
+            Some(namespace) => format!("{}/{}", namespace, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl std::str::FromStr for PackageUrl {
+    type Err = anyhow::Error;
+    fn from_str(value: &str) -> Result<Self> {
+        let error = || format_err!("Failed to parse package URL: {}", value);
+
+        let remainder = value.strip_prefix("pkg:").ok_or_else(error)?;
+        let remainder = remainder.split('#').next().unwrap_or("");
+        let mut parts = remainder.splitn(2, '?');
+        let remainder = parts.next().unwrap_or("");
+        let qualifiers = parts.next();
+
+        let mut parts = remainder.trim_matches('/').splitn(2, '/');
+        let package_type = parts.next().ok_or_else(error)?.to_lowercase();
+        let remainder = parts.next().ok_or_else(error)?;
+
+        let (remainder, version) = match remainder.rfind('@') {
+            Some(index) if index > 0 => (
+                &remainder[..index],
+                Some(percent_decode(&remainder[index + 1..])?),
+            ),
+            _ => (remainder, None),
+        };
+
+        let mut segments = remainder
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| percent_decode(segment))
+            .collect::<Result<Vec<_>>>()?;
+        let name = segments.pop().ok_or_else(error)?;
+        let namespace = if segments.is_empty() {
+            None
+        } else {
+            Some(segments.join("/"))
+        };
+
+        let mut repository_url = None;
+        for qualifier in qualifiers.unwrap_or("").split('&') {
+            if let Some(url) = qualifier.strip_prefix("repository_url=") {
+                repository_url = Some(percent_decode(url)?);
+            }
+        }
+
+        Ok(Self {
+            package_type,
+            namespace,
+            name,
+            version,
+            repository_url,
+        })
+    }
+}
+
+fn percent_decode(value: &str) -> Result<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = value
+                .get(index + 1..index + 3)
+                .ok_or(format_err!("Invalid percent encoding: {}", value))?;
+            decoded.push(
+                u8::from_str_radix(hex, 16)
+                    .map_err(|_| format_err!("Invalid percent encoding: {}", value))?,
+            );
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    Ok(String::from_utf8(decoded)?)
+}
+
+/// Read package URLs from a CycloneDX or SPDX JSON document.
+pub fn read(path: &std::path::PathBuf) -> Result<Vec<PackageUrl>> {
+    let document = read_document(&path)?;
+    parse(&document).context(format!("Failed to read SBOM file: {}", path.display()))
+}
+
+/// Read an SBOM JSON document.
+pub fn read_document(path: &std::path::PathBuf) -> Result<serde_json::Value> {
+    let file = std::fs::File::open(&path)
+        .context(format!("Failed to open SBOM file: {}", path.display()))?;
+    let reader = std::io::BufReader::new(file);
+    Ok(serde_json::from_reader(reader).context(format!(
+        "Failed to parse SBOM file as JSON: {}",
+        path.display()
+    ))?)
+}
+
+/// Returns true if the document is a CycloneDX document.
+pub fn is_cyclonedx(document: &serde_json::Value) -> bool {
+    document["bomFormat"] == "CycloneDX"
+}
+
+fn parse(document: &serde_json::Value) -> Result<Vec<PackageUrl>> {
+    let purls = if is_cyclonedx(&document) {
+        let mut purls = Vec::new();
+        get_cyclonedx_purls(&document["components"], &mut purls);
+        purls
+    } else if document["spdxVersion"].is_string() {
+        get_spdx_purls(&document)
+    } else {
+        return Err(format_err!(
+            "Unknown SBOM format. Expected CycloneDX or SPDX JSON document."
+        ));
+    };
+
+    purls.into_iter().map(|purl| purl.parse()).collect()
+}
+
+/// Collect package URLs from CycloneDX components, including nested components.
+fn get_cyclonedx_purls<'a>(components: &'a serde_json::Value, purls: &mut Vec<&'a str>) {
+    for component in components.as_array().into_iter().flatten() {
+        if let Some(purl) = component["purl"].as_str() {
+            purls.push(purl);
+        }
+        get_cyclonedx_purls(&component["components"], purls);
+    }
+}
+
+fn get_spdx_purls(document: &serde_json::Value) -> Vec<&str> {
+    document["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|package| package["externalRefs"].as_array().into_iter().flatten())
+        .filter(|reference| reference["referenceType"] == "purl")
+        .filter_map(|reference| reference["referenceLocator"].as_str())
+        .collect()
+}
+
+/// Returns the registry host name for a package URL, if any installed extension handles it.
+pub fn get_registry_host_name(
+    purl: &PackageUrl,
+    registries: &std::collections::BTreeMap<String, String>,
+) -> Option<String> {
+    if let Some(repository_url) = &purl.repository_url {
+        let host_name = url::Url::parse(&repository_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|host| host.to_string()));
+        if let Some(host_name) = host_name {
+            if registries.contains_key(&host_name) {
+                return Some(host_name);
+            }
+        }
+    }
+
+    let (_package_type, host_names) = PURL_TYPE_REGISTRY_HOST_NAMES
+        .iter()
+        .find(|(package_type, _host_names)| *package_type == purl.package_type)?;
+    host_names
+        .iter()
+        .find(|host_name| registries.contains_key(**host_name))
+        .map(|host_name| host_name.to_string())
+}
+
+/// Returns a package URL for a registry package. Returns None if the registry has no known package
+/// URL type.
+pub fn get_package_url(
+    registry_host_name: &str,
+    package_name: &str,
+    package_version: Option<&str>,
+) -> Option<String> {
+    let (package_type, _host_names) = PURL_TYPE_REGISTRY_HOST_NAMES
+        .iter()
+        .find(|(_package_type, host_names)| host_names.contains(&registry_host_name))?;
+    let separator = if *package_type == "maven" { ':' } else { '/' };
+    let name = package_name
+        .split(separator)
+        .map(|segment| segment.replace('%', "%25").replace('@', "%40"))
+        .collect::<Vec<_>>()
+        .join("/");
+    Some(match package_version {
+        Some(version) => format!("pkg:{}/{}@{}", package_type, name, version),
+        None => format!("pkg:{}/{}", package_type, name),
+    })
+}
+
+/// Group package URLs into dependencies specifications by registry.
+///
+/// Registries are the keys of `config.extensions.registries`. Returns the specifications and the
+/// package URL types which could not be mapped to a registry.
+pub fn get_dependencies_specs(
+    path: &std::path::PathBuf,
+    purls: &Vec<PackageUrl>,
+    registries: &std::collections::BTreeMap<String, String>,
+) -> (
+    Vec<vouch_lib::extension::DependenciesSpec>,
+    std::collections::BTreeSet<String>,
+) {
+    let mut dependencies =
+        std::collections::BTreeMap::<String, Vec<vouch_lib::extension::Dependency>>::new();
+    let mut unsupported_types = std::collections::BTreeSet::new();
+
+    for purl in purls {
+        let registry_host_name = match get_registry_host_name(&purl, &registries) {
+            Some(registry_host_name) => registry_host_name,
+            None => {
+                unsupported_types.insert(purl.package_type.clone());
+                continue;
+            }
+        };
+        let dependency = vouch_lib::extension::Dependency {
+            name: purl.registry_package_name(),
+            version: purl
+                .version
+                .clone()
+                .ok_or(vouch_lib::extension::common::VersionError::from_missing_version()),
+        };
+        let registry_dependencies = dependencies.entry(registry_host_name).or_default();
+        if !registry_dependencies.contains(&dependency) {
+            registry_dependencies.push(dependency);
+        }
+    }
+
+    let dependencies_specs = dependencies
+        .into_iter()
+        .map(
+            |(registry_host_name, dependencies)| vouch_lib::extension::DependenciesSpec {
+                path: path.clone(),
+                registry_host_name,
+                dependencies,
+            },
+        )
+        .collect();
+    (dependencies_specs, unsupported_types)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_url() -> Result<()> {
+        let result: PackageUrl =
+            "pkg:npm/%40angular/core@12.0.1?repository_url=https%3A%2F%2Fregistry.npmjs.org#src"
+                .parse()?;
+        let expected = PackageUrl {
+            package_type: "npm".to_string(),
+            namespace: Some("@angular".to_string()),
+            name: "core".to_string(),
+            version: Some("12.0.1".to_string()),
+            repository_url: Some("https://registry.npmjs.org".to_string()),
+        };
+        assert_eq!(result, expected);
+        assert_eq!(result.registry_package_name(), "@angular/core");
+        assert_eq!(
+            get_package_url("npmjs.com", "@angular/core", Some("12.0.1")),
+            Some("pkg:npm/%40angular/core@12.0.1".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cyclonedx_and_spdx_grouped_by_registry() -> Result<()> {
+        let cyclonedx = serde_json::json!({
+            "bomFormat": "CycloneDX",
+            "components": [
+                {
+                    "name": "numpy",
+                    "purl": "pkg:pypi/numpy@1.18.5",
+                    "components": [{"name": "left-pad", "purl": "pkg:npm/left-pad@1.3.0"}]
+                },
+                {"name": "zlib", "purl": "pkg:deb/debian/zlib@1.2.11"}
+            ]
+        });
+        let spdx = serde_json::json!({
+            "spdxVersion": "SPDX-2.2",
+            "packages": [{
+                "name": "numpy",
+                "externalRefs": [
+                    {"referenceType": "cpe23Type", "referenceLocator": "cpe:2.3:a:numpy"},
+                    {"referenceType": "purl", "referenceLocator": "pkg:pypi/numpy@1.18.5"}
+                ]
+            }]
+        });
+        let mut purls = parse(&cyclonedx)?;
+        purls.extend(parse(&spdx)?);
+
+        let registries = maplit::btreemap! {
+            "pypi.org".to_string() => "py".to_string(),
+            "npmjs.com".to_string() => "js".to_string(),
+        };
+        let path = std::path::PathBuf::from("/sbom.json");
+        let (result, unsupported_types) = get_dependencies_specs(&path, &purls, &registries);
+
+        let get_spec = |registry_host_name: &str, name: &str, version: &str| {
+            vouch_lib::extension::DependenciesSpec {
+                path: path.clone(),
+                registry_host_name: registry_host_name.to_string(),
+                dependencies: vec![vouch_lib::extension::Dependency {
+                    name: name.to_string(),
+                    version: Ok(version.to_string()),
+                }],
+            }
+        };
+        let expected = vec![
+            get_spec("npmjs.com", "left-pad", "1.3.0"),
+            get_spec("pypi.org", "numpy", "1.18.5"),
+        ];
+        assert_eq!(result, expected);
+        assert_eq!(unsupported_types, maplit::btreeset! {"deb".to_string()});
+        Ok(())
+    }
+}
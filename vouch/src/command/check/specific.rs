@@ -1,7 +1,8 @@
 use anyhow::Result;
 
+use super::report;
 use crate::common;
-use crate::common::StoreTransaction;
+use crate::common::{ContentHash, StoreTransaction};
 use crate::extension;
 use crate::review;
 
@@ -10,17 +11,50 @@ pub fn report(
     package_name: &str,
     package_version: &Option<String>,
     extension_names: &std::collections::BTreeSet<String>,
+    policy: &report::Policy,
     config: &common::config::Config,
     tx: &StoreTransaction,
 ) -> Result<()> {
     // TODO: Handle multiple registries.
-    let reviews = get_package_reviews(
+    let (reviews, quarantined_reviews): (
+        std::collections::BTreeSet<_>,
+        std::collections::BTreeSet<_>,
+    ) = get_package_reviews(
         package_name,
         package_version,
         &extension_names,
         &config,
         &tx,
-    )?;
+    )?
+    .into_iter()
+    .partition(|review| {
+        !policy
+            .quarantined_peers
+            .contains(review.peer.git_url.as_str())
+    });
+    if !quarantined_reviews.is_empty() {
+        println!(
+            "Hidden reviews from quarantined peers: {count}. \
+            Approve peers using: vouch peer approve <peer>\n",
+            count = quarantined_reviews.len()
+        );
+    }
+
+    let (reviews, unconfident_reviews): (
+        std::collections::BTreeSet<_>,
+        std::collections::BTreeSet<_>,
+    ) = reviews
+        .into_iter()
+        .partition(|review| policy.is_confidence_accepted(&review));
+    if let Some(min_confidence) = policy.min_confidence {
+        if !unconfident_reviews.is_empty() {
+            println!(
+                "Hidden reviews below minimum confidence ({min_confidence}): {count}.\n",
+                min_confidence = min_confidence,
+                count = unconfident_reviews.len()
+            );
+        }
+    }
     if reviews.is_empty() {
         println!("No reviews found.");
         let disabled_extension_names = extension::manage::get_disabled_names(&config)?;
@@ -32,6 +66,8 @@ pub fn report(
         }
     }
 
+    let conversations = Conversations::new(&reviews);
+
     let mut reviews_by_version = std::collections::BTreeMap::<_, Vec<&review::Review>>::new();
     for review in &reviews {
         match reviews_by_version.get_mut(&review.package.version) {
@@ -55,9 +91,17 @@ pub fn report(
         };
         reviews.sort();
 
+        let missing_criteria = get_missing_criteria(&reviews, &policy.required_criteria);
+        if !missing_criteria.is_empty() {
+            println!(
+                "Missing required criteria: {}\n",
+                missing_criteria.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+
         let mut reports = Vec::<_>::new();
         for review in reviews {
-            let report = get_review_report(&review)?;
+            let report = get_review_report(&review, &conversations)?;
             reports.push(report);
         }
         println!("{}", reports.join("\n\n"));
@@ -65,24 +109,166 @@ pub fn report(
     Ok(())
 }
 
-fn get_review_report(review: &review::Review) -> Result<String> {
+/// Returns the required criteria which none of the given reviews certify.
+fn get_missing_criteria(
+    reviews: &Vec<&review::Review>,
+    required_criteria: &std::collections::BTreeSet<String>,
+) -> std::collections::BTreeSet<String> {
+    required_criteria
+        .iter()
+        .filter(|criterion| {
+            !reviews
+                .iter()
+                .any(|review| review.criteria.contains(*criterion))
+        })
+        .cloned()
+        .collect()
+}
+
+fn get_review_report(review: &review::Review, conversations: &Conversations) -> Result<String> {
     let peer_url = if !review.peer.is_root() {
         format!("({})", review.peer.git_url)
     } else {
         "".to_string()
     };
 
+    let criteria = if review.criteria.is_empty() {
+        "none".to_string()
+    } else {
+        review
+            .criteria
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let verdict = format!(
+        "{summary} (confidence: {confidence})",
+        summary = review
+            .package_security
+            .as_ref()
+            .map(|summary| summary.to_string())
+            .unwrap_or("none".to_string()),
+        confidence = review
+            .review_confidence
+            .map(|confidence| confidence.to_string())
+            .unwrap_or("unknown".to_string())
+    );
+
     Ok(format!(
         "\
+Review: {review_hash}\n\
 Peer: {peer_alias} {peer_url}\n\
+Verdict: {verdict}\n\
+Criteria: {criteria}\n\
+Comments:\n\
 {comments_report}
 \n\n",
+        review_hash = review.content_hash(),
         peer_alias = review.peer.alias,
         peer_url = peer_url,
-        comments_report = serde_yaml::to_string(&review.comments)?,
+        verdict = verdict,
+        criteria = criteria,
+        comments_report = conversations.get_report(&review),
     ))
 }
 
+/// Comment threads across the reviews of all peers.
+struct Conversations<'a> {
+    /// Replies keyed by the content hash of the comment replied to.
+    replies:
+        std::collections::HashMap<String, Vec<(&'a review::Review, &'a review::comment::Comment)>>,
+
+    /// Content hashes of all known comments.
+    comment_hashes: std::collections::HashSet<String>,
+}
+
+impl<'a> Conversations<'a> {
+    fn new(reviews: &'a std::collections::BTreeSet<review::Review>) -> Self {
+        let mut replies = std::collections::HashMap::<_, Vec<_>>::new();
+        let mut comment_hashes = std::collections::HashSet::new();
+        for review in reviews {
+            for comment in &review.comments {
+                comment_hashes.insert(comment.content_hash());
+                if let Some(in_reply_to) = &comment.in_reply_to {
+                    replies
+                        .entry(in_reply_to.clone())
+                        .or_default()
+                        .push((review, comment));
+                }
+            }
+        }
+        Self {
+            replies,
+            comment_hashes,
+        }
+    }
+
+    /// Returns the review comments as conversations.
+    ///
+    /// Replies are rendered beneath the comments which they reply to, whichever peer authored
+    /// them. Replies to comments which are not known locally start their own conversation.
+    fn get_report(&self, review: &review::Review) -> String {
+        let mut lines = Vec::new();
+        let mut visited = std::collections::HashSet::new();
+        for comment in &review.comments {
+            let is_reply_to_known_comment = match &comment.in_reply_to {
+                Some(in_reply_to) => self.comment_hashes.contains(in_reply_to),
+                None => false,
+            };
+            if !is_reply_to_known_comment {
+                self.add_thread_lines(&review, &comment, 0, &mut visited, &mut lines);
+            }
+        }
+        if lines.is_empty() {
+            lines.push("  none".to_string());
+        }
+        lines.join("\n")
+    }
+
+    fn add_thread_lines(
+        &self,
+        review: &review::Review,
+        comment: &review::comment::Comment,
+        depth: usize,
+        visited: &mut std::collections::HashSet<String>,
+        lines: &mut Vec<String>,
+    ) {
+        let hash = comment.content_hash();
+        if !visited.insert(hash.clone()) {
+            return;
+        }
+
+        let indent = "  ".repeat(depth + 1);
+        let selection = match &comment.selection {
+            Some(selection) => format!(":{}-{}", selection.start.line, selection.end.line),
+            None => "".to_string(),
+        };
+        let author = if depth > 0 {
+            format!(" by {} ({})", review.peer.alias, review.package.version)
+        } else {
+            "".to_string()
+        };
+        lines.push(format!(
+            "{indent}- {summary} {path}{selection}{author} [{hash}]",
+            indent = indent,
+            summary = comment.summary,
+            path = comment.path.display(),
+            selection = selection,
+            author = author,
+            hash = hash
+        ));
+        for line in comment.message.lines() {
+            lines.push(format!("{}  {}", indent, line));
+        }
+
+        for (reply_review, reply) in self.replies.get(&hash).into_iter().flatten() {
+            self.add_thread_lines(&reply_review, &reply, depth + 1, visited, lines);
+        }
+    }
+}
+
 fn get_package_reviews(
     package_name: &str,
     package_version: &Option<String>,
@@ -110,3 +296,43 @@ fn get_package_reviews(
     .collect::<std::collections::BTreeSet<_>>();
     Ok(reviews)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_review(criteria: &[&str]) -> review::Review {
+        review::Review {
+            id: 1,
+            peer: crate::peer::Peer::default(),
+            package: crate::package::Package {
+                id: 1,
+                name: "numpy".to_string(),
+                version: "1.18.5".to_string(),
+                registries: std::collections::BTreeSet::new(),
+                artifact_hash: "artifact_hash".to_string(),
+            },
+            comments: std::collections::BTreeSet::new(),
+            criteria: criteria
+                .iter()
+                .map(|criterion| criterion.to_string())
+                .collect(),
+            package_security: None,
+            review_confidence: None,
+        }
+    }
+
+    #[test]
+    fn test_missing_criteria() {
+        let review_1 = get_review(&["safe-to-run"]);
+        let review_2 = get_review(&["reproducible"]);
+        let required_criteria = maplit::btreeset! {
+            "safe-to-run".to_string(),
+            "safe-to-deploy".to_string(),
+        };
+
+        let result = get_missing_criteria(&vec![&review_1, &review_2], &required_criteria);
+        let expected = maplit::btreeset! {"safe-to-deploy".to_string()};
+        assert_eq!(result, expected);
+    }
+}
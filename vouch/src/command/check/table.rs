@@ -1,7 +1,7 @@
 use super::report;
 use crate::review;
 use anyhow::Result;
-use prettytable::{self, cell};
+use prettytable;
 
 /// Generates and returns a table from a given extension dependency review report.
 pub fn get(dependency_reports: &Vec<report::DependencyReport>) -> Result<prettytable::Table> {
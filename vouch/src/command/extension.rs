@@ -17,6 +17,15 @@ pub enum Subcommands {
 
     /// Disable extension without deleting.
     Disable(DisableArguments),
+
+    /// Reload extension registries and capabilities from extension binaries.
+    Refresh(RefreshArguments),
+
+    /// List extensions.
+    List(ListArguments),
+
+    /// Update process extensions to their latest GitHub release.
+    Update(UpdateArguments),
 }
 
 pub fn run_subcommand(subcommand: &Subcommands) -> Result<()> {
@@ -37,6 +46,18 @@ pub fn run_subcommand(subcommand: &Subcommands) -> Result<()> {
             log::info!("Running command: extension disable");
             disable(&args)?;
         }
+        Subcommands::Refresh(args) => {
+            log::info!("Running command: extension refresh");
+            refresh(&args)?;
+        }
+        Subcommands::List(args) => {
+            log::info!("Running command: extension list");
+            list(&args)?;
+        }
+        Subcommands::Update(args) => {
+            log::info!("Running command: extension update");
+            update(&args)?;
+        }
     }
     Ok(())
 }
@@ -79,24 +100,29 @@ fn add(args: &AddArguments) -> Result<()> {
     }
     log::info!("Using extension bin directory: {}", bin_directory.display());
 
-    let extension_name = if args.name_or_url.contains("/") {
+    let url = if args.name_or_url.contains("/") {
         log::debug!("Identified argument as URL.");
         let url = args.name_or_url.clone();
         if let Some(url) = try_parse_user_url(&url)? {
             log::debug!("Sanitized URL: {}", url);
-            extension::manage::add_from_url(&url, &bin_directory)?
+            url
         } else {
             return Err(format_err!("Failed to parse URL: {}", url));
         }
     } else {
         log::debug!("Identified argument as name.");
         let name = extension::manage::clean_name(&args.name_or_url);
-        let url = get_url_from_name(&name)?;
-        extension::manage::add_from_url(&url, &bin_directory)?
+        get_url_from_name(&name)?
     };
+    let (extension_name, archive_url) = extension::manage::add_from_url(&url, &bin_directory)?;
 
     let mut config = common::config::Config::load()?;
     extension::manage::update_config(&mut config)?;
+    config.extensions.sources.insert(
+        extension_name.clone(),
+        common::config::ExtensionSource { url, archive_url },
+    );
+    config.dump()?;
 
     println!("Added extension: {}", extension_name);
     Ok(())
@@ -225,3 +251,136 @@ fn disable(args: &DisableArguments) -> Result<()> {
     println!("Disabled extension: {}", name);
     Ok(())
 }
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct RefreshArguments {
+    /// Extension name. Refreshes all process extensions if omitted.
+    pub name: Option<String>,
+}
+
+fn refresh(args: &RefreshArguments) -> Result<()> {
+    let name = args
+        .name
+        .as_ref()
+        .map(|name| extension::manage::clean_name(&name));
+    let mut config = common::config::Config::load()?;
+    let names = extension::manage::refresh(&name, &config)?;
+    extension::manage::update_config(&mut config)?;
+
+    for name in names {
+        println!("Refreshed extension: {}", name);
+    }
+    Ok(())
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct ListArguments {}
+
+fn list(_args: &ListArguments) -> Result<()> {
+    let mut config = common::config::Config::load()?;
+    extension::manage::update_config(&mut config)?;
+
+    let mut extensions = extension::manage::get_all_with_paths(&config)?;
+    extensions.sort_by_key(|(extension, process_path)| (extension.name(), process_path.clone()));
+
+    let mut table = prettytable::Table::new();
+    table.set_titles(prettytable::row![
+        "name",
+        "enabled",
+        "version",
+        "registries",
+        "type",
+        "path"
+    ]);
+    table.set_format(*prettytable::format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+    for (extension, process_path) in extensions {
+        let name = extension.name();
+        let enabled = extension::manage::is_enabled(&name, &config)?;
+        let (extension_type, path) = match process_path {
+            Some(path) => ("process", path.display().to_string()),
+            None => ("built-in", "".to_string()),
+        };
+        table.add_row(prettytable::row![
+            name,
+            if enabled { "yes" } else { "no" },
+            extension.version().unwrap_or_default(),
+            extension.registries().join(", "),
+            extension_type,
+            path
+        ]);
+    }
+    table.printstd();
+    Ok(())
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct UpdateArguments {
+    /// Extension name. Updates all process extensions if omitted.
+    pub name: Option<String>,
+}
+
+fn update(args: &UpdateArguments) -> Result<()> {
+    let mut config = common::config::Config::load()?;
+    extension::manage::update_config(&mut config)?;
+
+    let process_names = extension::manage::get_process_names()?;
+    let names = match &args.name {
+        Some(name) => {
+            let name = extension::manage::clean_name(&name);
+            if !process_names.contains(&name) {
+                return Err(format_err!(
+                    "Failed to find process extension: {}\n\
+                    Built-in extensions are updated with vouch.",
+                    name
+                ));
+            }
+            vec![name]
+        }
+        None => process_names.into_iter().collect(),
+    };
+
+    for name in names {
+        // Extensions installed without a recorded source are assumed to be official.
+        let url = match config.extensions.sources.get(&name) {
+            Some(source) => source.url.clone(),
+            None => get_url_from_name(&name)?,
+        };
+        let installed_archive_url = config
+            .extensions
+            .sources
+            .get(&name)
+            .map(|source| source.archive_url.clone());
+
+        match extension::manage::update(&name, &url, installed_archive_url.as_ref())? {
+            Some(archive_url) => {
+                config.extensions.sources.insert(
+                    name.clone(),
+                    common::config::ExtensionSource { url, archive_url },
+                );
+                config.dump()?;
+                println!("Updated extension: {}", name);
+            }
+            None => {
+                println!("Extension up-to-date: {}", name);
+            }
+        }
+    }
+
+    extension::manage::update_config(&mut config)?;
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use anyhow::{format_err, Result};
+use structopt::{self, StructOpt};
+
+use crate::common;
+use crate::common::StoreTransaction;
+use crate::package;
+use crate::peer;
+use crate::registry;
+use crate::review;
+use crate::store;
+
+#[derive(Debug, StructOpt, Clone)]
+pub enum Subcommands {
+    /// Rebuild index from review files and peers.
+    Rebuild(RebuildArguments),
+
+    /// Remove orphaned packages, registries and comments from index.
+    Gc(GcArguments),
+}
+
+pub fn run_subcommand(subcommand: &Subcommands) -> Result<()> {
+    match subcommand {
+        Subcommands::Rebuild(args) => {
+            log::info!("Running command: index rebuild");
+            rebuild(&args)?;
+        }
+        Subcommands::Gc(args) => {
+            log::info!("Running command: index gc");
+            gc(&args)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct RebuildArguments {}
+
+fn rebuild(_args: &RebuildArguments) -> Result<()> {
+    let paths = common::fs::DataPaths::new()?;
+    let backup_index_file = paths.index_directory.join("index.db.bak");
+
+    // Keep existing index until the new index has been built.
+    if paths.index_file.is_file() {
+        log::debug!(
+            "Moving existing index to backup file: {}",
+            backup_index_file.display()
+        );
+        std::fs::rename(&paths.index_file, &backup_index_file)?;
+    }
+
+    match rebuild_index(&paths) {
+        Ok(review_count) => {
+            if backup_index_file.is_file() {
+                std::fs::remove_file(&backup_index_file)?;
+            }
+            println!("Index rebuilt. Found {} reviews.", review_count);
+            Ok(())
+        }
+        Err(error) => {
+            log::debug!("Restoring index from backup file.");
+            if backup_index_file.is_file() {
+                if paths.index_file.is_file() {
+                    std::fs::remove_file(&paths.index_file)?;
+                }
+                std::fs::rename(&backup_index_file, &paths.index_file)?;
+            }
+            Err(error)
+        }
+    }
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct GcArguments {}
+
+fn gc(_args: &GcArguments) -> Result<()> {
+    let mut store = store::Store::from_root()?;
+    let tx = store.get_transaction()?;
+
+    let summary = store::index::clean(&tx)?;
+    println!(
+        "Removed orphaned index rows:\n\
+        packages: {packages}\n\
+        registries: {registries}\n\
+        comments: {comments}",
+        packages = summary.packages,
+        registries = summary.registries,
+        comments = summary.comments
+    );
+
+    tx.commit("Remove orphaned index rows.")?;
+    Ok(())
+}
+
+/// Build a new root index from review files and the peers submodule tree.
+///
+/// Returns the number of inserted reviews.
+fn rebuild_index(paths: &common::fs::DataPaths) -> Result<usize> {
+    let mut store = store::Store::from_root()?;
+    let tx = store.get_transaction()?;
+    store::index::setup(&tx)?;
+
+    let mut root_peer =
+        peer::index::get_root(&tx)?.ok_or(format_err!("Cant find root peer. Index corrupt."))?;
+
+    let mut review_count = 0;
+    for review in review::fs::get_all(&paths.reviews_directory)? {
+        insert_review(&review, &root_peer, &tx)?;
+        review_count += 1;
+    }
+    review_count += rebuild_peer_subtree(&mut root_peer, &paths.root_directory, &tx)?;
+
+    tx.commit("Rebuild index.")?;
+    Ok(review_count)
+}
+
+/// Insert child peers of the given parent peer and their reviews. Recurses into checked out peers.
+///
+/// Returns the number of inserted reviews.
+fn rebuild_peer_subtree(
+    parent_peer: &mut peer::Peer,
+    parent_peer_directory: &std::path::PathBuf,
+    tx: &StoreTransaction,
+) -> Result<usize> {
+    let mut review_count = 0;
+    for (git_url, peer_directory) in peer::fs::get_child_peers(&parent_peer_directory)? {
+        let existing_peer = peer::index::get(
+            &peer::index::Fields {
+                git_url: Some(&git_url),
+                ..Default::default()
+            },
+            &tx,
+        )?;
+        if !existing_peer.is_empty() {
+            log::debug!("Skipping peer which is already indexed: {}", git_url);
+            continue;
+        }
+
+        let alias = peer::index::get_new_alias(&git_url, &tx)?;
+        let mut peer = peer::index::insert(&alias, &git_url, Some(&mut *parent_peer), &tx)?;
+
+        if git2::Repository::open(&peer_directory).is_err() {
+            log::warn!(
+                "Peer repository is not checked out, reviews not indexed: {}",
+                git_url
+            );
+            continue;
+        }
+
+        let peer_paths = common::fs::DataPaths::from_root_directory(&peer_directory)?;
+        for review in review::fs::get_all(&peer_paths.reviews_directory)? {
+            insert_review(&review, &peer, &tx)?;
+            review_count += 1;
+        }
+        review_count += rebuild_peer_subtree(&mut peer, &peer_directory, &tx)?;
+    }
+    Ok(review_count)
+}
+
+/// Insert a review parsed from a review file into the index.
+fn insert_review(
+    review: &review::Review,
+    peer: &peer::Peer,
+    tx: &StoreTransaction,
+) -> Result<review::Review> {
+    let mut registries = std::collections::BTreeSet::new();
+    for registry in &review.package.registries {
+        registries.insert(registry::index::ensure(
+            &registry.host_name,
+            &registry.human_url,
+            &registry.artifact_url,
+            &tx,
+        )?);
+    }
+    let package = package::index::ensure(
+        &review.package.name,
+        &review.package.version,
+        &registries,
+        &review.package.artifact_hash,
+        &tx,
+    )?;
+
+    let mut comments = std::collections::BTreeSet::new();
+    for comment in &review.comments {
+        comments.insert(review::comment::index::insert(
+            &comment.path,
+            &comment.summary,
+            &comment.message,
+            &comment.selection,
+            &comment.in_reply_to,
+            &tx,
+        )?);
+    }
+    Ok(review::index::insert(
+        &comments,
+        &review.criteria,
+        &review.package_security,
+        &review.review_confidence,
+        &peer,
+        &package,
+        &tx,
+    )?)
+}
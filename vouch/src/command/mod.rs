@@ -4,6 +4,7 @@ use structopt::{self, StructOpt};
 mod check;
 mod config;
 mod extension;
+mod index;
 mod peer;
 mod review;
 mod setup;
@@ -45,6 +46,11 @@ pub fn run_command(command: Command) -> Result<()> {
             setup::is_complete()?;
             extension::run_subcommand(&args)?;
         }
+        Command::Index(args) => {
+            log::info!("Running command: index");
+            setup::is_complete()?;
+            index::run_subcommand(&args)?;
+        }
     }
     Ok(())
 }
@@ -80,6 +86,10 @@ pub enum Command {
     /// Manage extensions.
     #[structopt(name = "extension")]
     Extension(extension::Subcommands),
+
+    /// Manage the local index.
+    #[structopt(name = "index")]
+    Index(index::Subcommands),
 }
 
 #[derive(Debug, StructOpt, Clone)]
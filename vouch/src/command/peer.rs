@@ -14,6 +14,21 @@ pub enum Subcommands {
 
     /// Remove peer.
     Remove(RemoveArguments),
+
+    /// List peers as a tree.
+    List(ListArguments),
+
+    /// Show peer details.
+    Show(ShowArguments),
+
+    /// Block peer. Removes the peer and never merges its reviews or sub-peers.
+    Block(BlockArguments),
+
+    /// Unblock peer.
+    Unblock(UnblockArguments),
+
+    /// Approve a quarantined peer so that its reviews are used by check.
+    Approve(ApproveArguments),
 }
 
 pub fn run_subcommand(subcommand: &Subcommands) -> Result<()> {
@@ -26,6 +41,26 @@ pub fn run_subcommand(subcommand: &Subcommands) -> Result<()> {
             log::info!("Running command: peer remove");
             remove(&args)?;
         }
+        Subcommands::List(args) => {
+            log::info!("Running command: peer list");
+            list(&args)?;
+        }
+        Subcommands::Show(args) => {
+            log::info!("Running command: peer show");
+            show(&args)?;
+        }
+        Subcommands::Block(args) => {
+            log::info!("Running command: peer block");
+            block(&args)?;
+        }
+        Subcommands::Unblock(args) => {
+            log::info!("Running command: peer unblock");
+            unblock(&args)?;
+        }
+        Subcommands::Approve(args) => {
+            log::info!("Running command: peer approve");
+            approve(&args)?;
+        }
     }
     Ok(())
 }
@@ -40,15 +75,55 @@ pub struct AddArguments {
     /// Git repository URL.
     #[structopt(name = "git-url", parse(try_from_str = crate::common::GitUrl::try_from))]
     pub git_url: crate::common::GitUrl,
+
+    /// Maximum depth of imported sub-peers. Zero imports the peer's own reviews only.
+    #[structopt(long = "max-depth")]
+    pub max_depth: Option<usize>,
+
+    /// Only import sub-peers with this git URL. Can be given multiple times.
+    #[structopt(long = "allow-peer", number_of_values = 1)]
+    pub allow_peers: Vec<String>,
+
+    /// Do not import sub-peers with this git URL. Can be given multiple times.
+    #[structopt(long = "deny-peer", number_of_values = 1)]
+    pub deny_peers: Vec<String>,
+
+    /// Only import reviews of packages from this registry. Can be given multiple times.
+    #[structopt(long = "allow-registry", number_of_values = 1)]
+    pub allow_registries: Vec<String>,
+
+    /// Do not import reviews of packages from this registry. Can be given multiple times.
+    #[structopt(long = "deny-registry", number_of_values = 1)]
+    pub deny_registries: Vec<String>,
+}
+
+impl AddArguments {
+    fn get_peer_options(&self) -> common::config::PeerOptions {
+        common::config::PeerOptions {
+            max_depth: self.max_depth,
+            allow_peers: self.allow_peers.iter().cloned().collect(),
+            deny_peers: self.deny_peers.iter().cloned().collect(),
+            allow_registries: self.allow_registries.iter().cloned().collect(),
+            deny_registries: self.deny_registries.iter().cloned().collect(),
+        }
+    }
 }
 
 fn add(args: &AddArguments) -> Result<()> {
     let mut store = store::Store::from_root()?;
     let mut tx = store.get_transaction()?;
-    let config = common::config::Config::load()?;
+    let mut config = common::config::Config::load()?;
+
+    if config.blocklist.is_blocked(&args.git_url) {
+        return Err(format_err!(
+            "Not adding peer: given git URL is blocked. \
+            \nUnblock using: vouch peer unblock {}",
+            args.git_url
+        ));
+    }
 
-    if let Some(root_git_url) = config.core.root_git_url {
-        if args.git_url == root_git_url {
+    if let Some(root_git_url) = &config.core.root_git_url {
+        if &args.git_url == root_git_url {
             return Err(format_err!(
                 "Not adding peer: given git URL is already set as the root git URL. \
             \nSee config field: core.root-git-url"
@@ -77,6 +152,13 @@ fn add(args: &AddArguments) -> Result<()> {
             // Peer exist in the index and has root as parent.
             // Peer can't move further up the peer tree.
             // Nothing more to do.
+            if args.get_peer_options() != common::config::PeerOptions::default() {
+                println!(
+                    "Peer already followed. Options not changed. \
+                    Set options using: vouch config peers.{alias}.<option> <value>",
+                    alias = peer.alias
+                );
+            }
             return Ok(());
         }
 
@@ -87,11 +169,28 @@ fn add(args: &AddArguments) -> Result<()> {
 
     let alias = peer::index::get_new_alias(&args.git_url, &mut tx)?;
     let peer = peer::index::insert(&alias, &args.git_url, Some(&mut root_peer), &tx)?;
+    let options = args.get_peer_options();
+
     let mut peer_store = store::Store::from_peer(&vec![root_peer, peer])?;
     let peer_index_tx = peer_store.get_transaction()?;
-    store::index::merge(&args.git_url, &peer_index_tx, &tx)?;
+    store::index::merge(
+        &args.git_url,
+        &options,
+        &config.blocklist,
+        &peer_index_tx,
+        &tx,
+    )?;
+    store::index::clean(&tx)?;
 
     tx.commit(format!("Add peer: {}", &args.git_url).as_str())?;
+
+    // Options given on the command line are kept for subsequent syncs.
+    if options == common::config::PeerOptions::default() {
+        config.peers.options.remove(&alias);
+    } else {
+        config.peers.options.insert(alias, options);
+    }
+    config.dump()?;
     Ok(())
 }
 
@@ -102,30 +201,30 @@ fn add(args: &AddArguments) -> Result<()> {
     global_settings = &[structopt::clap::AppSettings::DisableVersion]
 )]
 pub struct RemoveArguments {
-    /// Git repository URL.
-    #[structopt(name = "git-url", parse(try_from_str = crate::common::GitUrl::try_from))]
-    pub git_url: crate::common::GitUrl,
+    /// Peer alias or git repository URL.
+    #[structopt(name = "peer")]
+    pub peer: String,
 }
 
 fn remove(args: &RemoveArguments) -> Result<()> {
     let mut store = store::Store::from_root()?;
     let mut tx = store.get_transaction()?;
 
-    let target_peer = peer::index::get(
-        &peer::index::Fields {
-            git_url: Some(&args.git_url),
-            ..Default::default()
-        },
-        &tx,
-    )?
-    .into_iter()
-    .next()
-    .ok_or(format_err!(
+    let target_peer = peer::index::get_by_alias_or_git_url(&args.peer, &tx)?.ok_or(format_err!(
         "Failed to find peer for removal: {}",
-        &args.git_url
+        &args.peer
     ))?;
+    if target_peer.is_root() {
+        return Err(format_err!("Cannot remove root peer."));
+    }
 
     remove_peer_subtree(&target_peer, &mut tx)?;
+    store::index::clean(&tx)?;
+
+    let mut config = common::config::Config::load()?;
+    if config.peers.options.remove(&target_peer.alias).is_some() {
+        config.dump()?;
+    }
 
     tx.commit(
         format!(
@@ -166,3 +265,358 @@ fn remove_peer_subtree(target_peer: &peer::Peer, tx: &mut common::StoreTransacti
     }
     Ok(())
 }
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct ListArguments {}
+
+fn list(_args: &ListArguments) -> Result<()> {
+    let mut store = store::Store::from_root()?;
+    let tx = store.get_transaction()?;
+
+    let root_peer =
+        peer::index::get_root(&tx)?.ok_or(format_err!("Cant find root peer. Index corrupt."))?;
+
+    // Group peers by parent so that the tree can be printed depth first.
+    let mut child_peers = std::collections::BTreeMap::<_, Vec<peer::Peer>>::new();
+    for peers in peer::index::get_breadth_first_child_peers(&root_peer, &tx)? {
+        for peer in peers {
+            if let Some(parent_id) = peer.parent_id {
+                child_peers.entry(parent_id).or_default().push(peer);
+            }
+        }
+    }
+    for peers in child_peers.values_mut() {
+        peers.sort();
+    }
+
+    let config = common::config::Config::load()?;
+    let mut lines = vec![get_peer_summary(&vec![root_peer.clone()], &config, &tx)?];
+    add_tree_lines(&vec![root_peer], "", &child_peers, &config, &tx, &mut lines)?;
+    println!("{}", lines.join("\n"));
+    Ok(())
+}
+
+/// Append tree lines for the children of the last peer in the given branch.
+fn add_tree_lines(
+    peer_branch: &Vec<peer::Peer>,
+    prefix: &str,
+    child_peers: &std::collections::BTreeMap<common::index::ID, Vec<peer::Peer>>,
+    config: &common::config::Config,
+    tx: &common::StoreTransaction,
+    lines: &mut Vec<String>,
+) -> Result<()> {
+    let peer = peer_branch
+        .last()
+        .ok_or(format_err!("Empty peer branch."))?;
+    let children = match child_peers.get(&peer.id) {
+        Some(children) => children,
+        None => return Ok(()),
+    };
+    for (index, child_peer) in children.iter().enumerate() {
+        let is_last = index + 1 == children.len();
+        let mut child_branch = peer_branch.clone();
+        child_branch.push(child_peer.clone());
+
+        lines.push(format!(
+            "{prefix}{connector}{summary}",
+            prefix = prefix,
+            connector = if is_last { "└── " } else { "├── " },
+            summary = get_peer_summary(&child_branch, &config, &tx)?
+        ));
+        let child_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
+        add_tree_lines(
+            &child_branch,
+            &child_prefix,
+            &child_peers,
+            &config,
+            &tx,
+            lines,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns a single line summary of the last peer in the given branch.
+fn get_peer_summary(
+    peer_branch: &Vec<peer::Peer>,
+    config: &common::config::Config,
+    tx: &common::StoreTransaction,
+) -> Result<String> {
+    let peer = peer_branch
+        .last()
+        .ok_or(format_err!("Empty peer branch."))?;
+    let review_count = get_review_count(&peer, &tx)?;
+    if peer.is_root() {
+        return Ok(format!(
+            "{alias} (reviews: {review_count})",
+            alias = peer.alias,
+            review_count = review_count
+        ));
+    }
+    Ok(format!(
+        "{alias} {git_url} (synced: {last_sync}, reviews: {review_count}){quarantined}",
+        alias = peer.alias,
+        git_url = peer.git_url,
+        last_sync = get_last_sync_description(&peer_branch)?,
+        review_count = review_count,
+        quarantined = if is_quarantined(&peer_branch, &config) {
+            " [quarantined]"
+        } else {
+            ""
+        }
+    ))
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct ShowArguments {
+    /// Peer alias or git repository URL.
+    #[structopt(name = "peer")]
+    pub peer: String,
+}
+
+fn show(args: &ShowArguments) -> Result<()> {
+    let mut store = store::Store::from_root()?;
+    let tx = store.get_transaction()?;
+
+    let peer = peer::index::get_by_alias_or_git_url(&args.peer, &tx)?
+        .ok_or(format_err!("Failed to find peer: {}", &args.peer))?;
+    let peer_branch = peer::index::get_peer_branch(&peer, &tx)?;
+
+    let parent = match peer_branch.len() {
+        0 | 1 => "none".to_string(),
+        length => peer_branch[length - 2].alias.clone(),
+    };
+
+    let mut child_aliases = peer::index::get(
+        &peer::index::Fields {
+            parent_id: Some(peer.id),
+            ..Default::default()
+        },
+        &tx,
+    )?
+    .into_iter()
+    .map(|child_peer| child_peer.alias)
+    .collect::<Vec<_>>();
+    child_aliases.sort();
+    let child_aliases = if child_aliases.is_empty() {
+        "none".to_string()
+    } else {
+        child_aliases.join(", ")
+    };
+
+    let last_sync = if peer.is_root() {
+        "n/a".to_string()
+    } else {
+        get_last_sync_description(&peer_branch)?
+    };
+
+    let config = common::config::Config::load()?;
+    let options = config.peers.get_options(&peer.alias);
+    let options = if options == common::config::PeerOptions::default() {
+        "none".to_string()
+    } else {
+        serde_json::to_string(&options)?
+    };
+
+    println!(
+        "\
+Alias: {alias}\n\
+Git URL: {git_url}\n\
+Parent: {parent}\n\
+Depth: {depth}\n\
+Last sync: {last_sync}\n\
+Reviews: {review_count}\n\
+Child peers: {child_aliases}\n\
+Quarantined: {quarantined}\n\
+Options: {options}",
+        alias = peer.alias,
+        git_url = peer.git_url,
+        parent = parent,
+        depth = peer_branch.len() - 1,
+        last_sync = last_sync,
+        review_count = get_review_count(&peer, &tx)?,
+        child_aliases = child_aliases,
+        quarantined = if is_quarantined(&peer_branch, &config) {
+            "yes"
+        } else {
+            "no"
+        },
+        options = options
+    );
+    Ok(())
+}
+
+/// Returns true if the last peer in the given branch is quarantined.
+///
+/// See `peer::index::get_quarantined`.
+fn is_quarantined(peer_branch: &Vec<peer::Peer>, config: &common::config::Config) -> bool {
+    match peer_branch.last() {
+        Some(peer) => {
+            config.quarantine.enabled
+                && peer_branch.len() > 2
+                && !config
+                    .quarantine
+                    .approved_peers
+                    .contains(peer.git_url.as_str())
+        }
+        None => false,
+    }
+}
+
+fn get_review_count(peer: &peer::Peer, tx: &common::StoreTransaction) -> Result<usize> {
+    Ok(review::index::get(
+        &review::index::Fields {
+            peer: Some(&peer),
+            ..Default::default()
+        },
+        &tx,
+    )?
+    .len())
+}
+
+fn get_last_sync_description(peer_branch: &Vec<peer::Peer>) -> Result<String> {
+    Ok(match peer::fs::get_last_sync_time(&peer_branch)? {
+        Some(time) => get_elapsed_description(time),
+        None => "never".to_string(),
+    })
+}
+
+/// Describe the time elapsed since the given time, e.g. "3 days ago".
+fn get_elapsed_description(time: std::time::SystemTime) -> String {
+    let seconds = match time.elapsed() {
+        Ok(elapsed) => elapsed.as_secs(),
+        Err(_) => 0,
+    };
+    let (count, unit) = if seconds < 60 {
+        return "just now".to_string();
+    } else if seconds < 60 * 60 {
+        (seconds / 60, "minute")
+    } else if seconds < 60 * 60 * 24 {
+        (seconds / (60 * 60), "hour")
+    } else {
+        (seconds / (60 * 60 * 24), "day")
+    };
+    format!(
+        "{count} {unit}{plural} ago",
+        count = count,
+        unit = unit,
+        plural = if count == 1 { "" } else { "s" }
+    )
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct BlockArguments {
+    /// Git repository URL.
+    #[structopt(name = "git-url", parse(try_from_str = crate::common::GitUrl::try_from))]
+    pub git_url: crate::common::GitUrl,
+}
+
+fn block(args: &BlockArguments) -> Result<()> {
+    let mut store = store::Store::from_root()?;
+    let mut tx = store.get_transaction()?;
+    let mut config = common::config::Config::load()?;
+
+    if let Some(target_peer) = peer::index::get(
+        &peer::index::Fields {
+            git_url: Some(&args.git_url),
+            ..Default::default()
+        },
+        &tx,
+    )?
+    .into_iter()
+    .next()
+    {
+        if target_peer.is_root() {
+            return Err(format_err!("Cannot block root peer."));
+        }
+        remove_peer_subtree(&target_peer, &mut tx)?;
+        store::index::clean(&tx)?;
+        tx.commit(
+            format!(
+                "Block peer: {alias} ({git_url})",
+                alias = target_peer.alias,
+                git_url = target_peer.git_url
+            )
+            .as_str(),
+        )?;
+        config.peers.options.remove(&target_peer.alias);
+    }
+
+    config.blocklist.insert(&args.git_url);
+    config.dump()?;
+    println!("Blocked peer: {}", args.git_url);
+    Ok(())
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct UnblockArguments {
+    /// Git repository URL.
+    #[structopt(name = "git-url", parse(try_from_str = crate::common::GitUrl::try_from))]
+    pub git_url: crate::common::GitUrl,
+}
+
+fn unblock(args: &UnblockArguments) -> Result<()> {
+    let mut config = common::config::Config::load()?;
+    if !config.blocklist.remove(&args.git_url) {
+        return Err(format_err!("Peer is not blocked: {}", args.git_url));
+    }
+    config.dump()?;
+    println!(
+        "Unblocked peer: {}\n\
+        Reviews are merged when the peers which follow it are next updated.",
+        args.git_url
+    );
+    Ok(())
+}
+
+#[derive(Debug, StructOpt, Clone)]
+#[structopt(
+    name = "no_version",
+    no_version,
+    global_settings = &[structopt::clap::AppSettings::DisableVersion]
+)]
+pub struct ApproveArguments {
+    /// Peer alias or git repository URL.
+    #[structopt(name = "peer")]
+    pub peer: String,
+}
+
+fn approve(args: &ApproveArguments) -> Result<()> {
+    let mut store = store::Store::from_root()?;
+    let tx = store.get_transaction()?;
+    let mut config = common::config::Config::load()?;
+
+    let peer = peer::index::get_by_alias_or_git_url(&args.peer, &tx)?
+        .ok_or(format_err!("Failed to find peer: {}", &args.peer))?;
+    config
+        .quarantine
+        .approved_peers
+        .insert(peer.git_url.to_string());
+    config.dump()?;
+    println!(
+        "Approved peer: {alias} ({git_url})",
+        alias = peer.alias,
+        git_url = peer.git_url
+    );
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 
 use anyhow::{format_err, Result};
-use common::StoreTransaction;
+use common::{ContentHash, StoreTransaction};
 use structopt::{self, StructOpt};
 
 use crate::common;
@@ -31,6 +31,10 @@ pub struct Arguments {
     /// Example values: py, js, rs
     #[structopt(long = "extension", short = "e", name = "name")]
     pub extension_names: Option<Vec<String>>,
+
+    /// Share the review without asking for confirmation.
+    #[structopt(long = "yes", short = "y")]
+    pub yes: bool,
 }
 
 pub fn run_command(args: &Arguments) -> Result<()> {
@@ -60,51 +64,77 @@ pub fn run_command(args: &Arguments) -> Result<()> {
 
     let reviews_directory =
         review::tool::ensure_reviews_directory(&workspace_manifest.workspace_path)?;
-    let active_review_file = review::active::ensure(&review, &reviews_directory)?;
+    let criteria_names = config
+        .review_criteria
+        .descriptions
+        .keys()
+        .cloned()
+        .collect();
+    let active_review_file = review::active::ensure(&review, &criteria_names, &reviews_directory)?;
 
     println!("Starting review tool.");
     review::tool::run(&workspace_manifest.workspace_path, &config)?;
-    review.comments = get_comments(&active_review_file, &tx)?;
+    let active_review = review::active::parse(&active_review_file)?;
+    review.comments = get_comments(&active_review, &tx)?;
+    review.criteria = active_review.certified_criteria();
+    review.package_security = active_review.package_security.clone();
+    review.review_confidence = active_review.review_confidence;
     println!(
         "Review tool closed. Fund {} review comments.",
         review.comments.len()
     );
+    if !review.criteria.is_empty() {
+        println!(
+            "Certified criteria: {}",
+            review
+                .criteria
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if let Some(package_security) = &review.package_security {
+        println!("Package verdict: {}", package_security);
+    }
 
-    if review.comments.is_empty() {
-        println!("No review comments found. Review saved as ongoing.");
+    if review.comments.is_empty() && review.criteria.is_empty() && review.package_security.is_none()
+    {
+        println!("No review comments, criteria or verdict found. Review saved as ongoing.");
         return Ok(());
     }
 
-    if dialoguer::Confirm::new()
-        .with_prompt("Is the review ready to share?")
-        .interact()?
+    if args.yes
+        || dialoguer::Confirm::new()
+            .with_prompt("Is the review ready to share?")
+            .interact()?
     {
         review::store(&review, &tx)?;
         let commit_message = get_commit_message(&review.package, &edit_mode)?;
         tx.commit(&commit_message)?;
-        println!("Review committed.");
+        println!("Review committed: {}", review.content_hash());
 
-        review::workspace::remove(&workspace_manifest)?;
+        review::workspace::remove(&workspace_manifest, &common::fs::DataPaths::new()?)?;
     } else {
         println!("Not committing review. Review saved as ongoing.");
     }
     Ok(())
 }
 
-/// Parse user comments from active review file and insert into index.
+/// Insert user comments from active review file into index.
 fn get_comments(
-    active_review_file: &std::path::PathBuf,
+    active_review: &review::active::ActiveReview,
     tx: &StoreTransaction,
 ) -> Result<std::collections::BTreeSet<review::comment::Comment>> {
-    let comments = review::active::parse(&active_review_file)?;
-
     let mut inserted_comments = std::collections::BTreeSet::<_>::new();
-    for comment in comments {
+    for comment in &active_review.comments {
         let comment = review::comment::index::insert(
             &comment.path,
             &comment.summary,
             &comment.message,
             &comment.selection,
+            &comment.in_reply_to,
             &tx,
         )?;
         inserted_comments.insert(comment);
@@ -196,6 +226,7 @@ fn setup_existing_review(
         &review.package.version,
         &registry.host_name,
         &registry.artifact_url,
+        &common::fs::DataPaths::new()?,
     )?;
     Ok(Some((review.clone(), workspace_manifest)))
 }
@@ -331,6 +362,7 @@ fn ensure_package_setup(
                 &package.version,
                 &registry.host_name,
                 &registry.artifact_url,
+                &common::fs::DataPaths::new()?,
             )?;
             (package, workspace_manifest)
         }
@@ -346,6 +378,7 @@ fn ensure_package_setup(
                 &package_version,
                 &registry.host_name,
                 &registry.artifact_url,
+                &common::fs::DataPaths::new()?,
             )?;
             let package = package::index::insert(
                 &package_name,
@@ -368,6 +401,9 @@ fn get_insert_empty_review(
         peer::index::get_root(&tx)?.ok_or(format_err!("Cant find root peer. Index corrupt."))?;
     let unset_review = review::index::insert(
         &std::collections::BTreeSet::<review::comment::Comment>::new(),
+        &std::collections::BTreeSet::new(),
+        &None,
+        &None,
         &root_peer,
         &package,
         &tx,
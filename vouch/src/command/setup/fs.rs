@@ -1,5 +1,6 @@
 use anyhow::{format_err, Result};
 use git2;
+use std::convert::TryFrom;
 use std::io::Write;
 
 use crate::common;
@@ -84,10 +85,18 @@ fn setup_top_level_peers(paths: &common::fs::DataPaths) -> Result<()> {
     for submodule in submodules {
         let path = submodule.path();
         log::debug!("Updating top level peer submodule: {}", path.display());
-        common::fs::git(
-            vec!["submodule", "update", "--init", "--depth", "1"],
-            &paths.root_directory.join(path),
-        )?;
+        let is_local = submodule
+            .url()
+            .and_then(|url| common::GitUrl::try_from(url).ok())
+            .map_or(false, |git_url| git_url.is_local());
+
+        let mut args = Vec::new();
+        if is_local {
+            // Git refuses to clone local submodules unless file transport is explicitly allowed.
+            args.extend(vec!["-c", "protocol.file.allow=always"]);
+        }
+        args.extend(vec!["submodule", "update", "--init", "--depth", "1"]);
+        common::fs::git(args, &paths.root_directory.join(path))?;
     }
     Ok(())
 }
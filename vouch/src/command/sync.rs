@@ -41,6 +41,7 @@ pub fn run_command(_args: &Arguments) -> Result<()> {
             println!("All peers up-to-date.");
         }
     } else {
+        store::index::clean(&tx)?;
         let message = get_commit_message(&updated_peers)?;
         tx.commit(message.as_str())?;
     }
@@ -56,7 +57,11 @@ fn update_peer(peer: &peer::Peer, tx: &mut common::StoreTransaction) -> Result<O
     println!("Fetching: {}", peer.git_url.to_string());
     let update_found = peer::fs::fetch_update(&peer, tx)?;
     if !update_found {
-        return Ok(None);
+        // Peer options or the blocklist may have changed since the last merge.
+        let config = common::config::Config::load()?;
+        let options = config.peers.get_options(&peer.alias);
+        let pruned = prune_excluded_sub_peers(&peer, 0, &options, &config.blocklist, tx)?;
+        return Ok(if pruned { Some(peer.clone()) } else { None });
     }
 
     remove_index_peer_subtree(&peer, tx)?;
@@ -101,6 +106,40 @@ fn update_remote() -> Result<()> {
     Ok(())
 }
 
+/// Remove sub-peers, and their reviews, which are excluded by the given peer options or blocklist.
+///
+/// The given peer is at the given depth below the followed peer. Returns true if any sub-peer was
+/// removed.
+fn prune_excluded_sub_peers(
+    peer: &peer::Peer,
+    depth: usize,
+    options: &common::config::PeerOptions,
+    blocklist: &common::config::Blocklist,
+    tx: &mut common::StoreTransaction,
+) -> Result<bool> {
+    let child_peers = peer::index::get(
+        &peer::index::Fields {
+            parent_id: Some(peer.id),
+            ..Default::default()
+        },
+        &tx,
+    )?;
+
+    let mut pruned = false;
+    for child_peer in child_peers {
+        if blocklist.is_blocked(&child_peer.git_url)
+            || !options.is_peer_allowed(&child_peer.git_url, depth + 1)
+        {
+            log::debug!("Removing excluded peer: {}", child_peer.git_url);
+            remove_index_peer_subtree(&child_peer, tx)?;
+            pruned = true;
+        } else {
+            pruned |= prune_excluded_sub_peers(&child_peer, depth + 1, &options, &blocklist, tx)?;
+        }
+    }
+    Ok(pruned)
+}
+
 fn remove_index_peer_subtree(
     target_peer: &peer::Peer,
     tx: &mut common::StoreTransaction,
@@ -139,9 +178,18 @@ fn merge_updated_peer_subtree(
         peer::index::get_root(&tx)?.ok_or(format_err!("Cant find root peer. Index corrupt."))?;
     let peer = peer::index::insert(&peer.alias, &peer.git_url, Some(&mut root_peer), &tx)?;
 
+    let config = common::config::Config::load()?;
+    let options = config.peers.get_options(&peer.alias);
+
     let mut peer_store = store::Store::from_peer(&vec![root_peer.clone(), peer.clone()])?;
     let peer_index_tx = peer_store.get_transaction()?;
-    store::index::merge(&peer.git_url, &peer_index_tx, &tx)?;
+    store::index::merge(
+        &peer.git_url,
+        &options,
+        &config.blocklist,
+        &peer_index_tx,
+        &tx,
+    )?;
 
     Ok(peer)
 }
@@ -0,0 +1,135 @@
+use anyhow::{format_err, Result};
+use std::convert::TryFrom;
+
+/// Peers whose reviews and sub-peers are never merged.
+///
+/// Peers are blocked by git URL only. Reviews are not signed yet, so there are no signing keys to
+/// block by.
+#[derive(
+    Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize,
+)]
+pub struct Blocklist {
+    /// Blocked peer git URLs.
+    #[serde(default)]
+    pub peers: std::collections::BTreeSet<String>,
+}
+
+impl Blocklist {
+    /// Returns true if the git URL is blocked. Entries are compared as parsed git URLs.
+    pub fn is_blocked(&self, git_url: &crate::common::GitUrl) -> bool {
+        self.peers
+            .iter()
+            .any(|peer| is_matching_entry(&peer, &git_url))
+    }
+
+    pub fn insert(&mut self, git_url: &crate::common::GitUrl) {
+        self.peers.insert(git_url.to_string());
+    }
+
+    /// Unblock git URL. Returns false if the git URL was not blocked.
+    pub fn remove(&mut self, git_url: &crate::common::GitUrl) -> bool {
+        let peers_count = self.peers.len();
+        self.peers
+            .retain(|peer| !is_matching_entry(&peer, &git_url));
+        self.peers.len() != peers_count
+    }
+}
+
+/// Returns true if the blocklist entry parses to the given git URL.
+fn is_matching_entry(entry: &str, git_url: &crate::common::GitUrl) -> bool {
+    crate::common::GitUrl::try_from(entry).map_or(false, |entry| &entry == git_url)
+}
+
+fn get_regex() -> Result<regex::Regex> {
+    Ok(regex::Regex::new(r"^blocklist\.(.*)")?)
+}
+
+pub fn is_match(name: &str) -> Result<bool> {
+    Ok(get_regex()?.is_match(name))
+}
+
+pub fn set(blocklist: &mut Blocklist, name: &str, value: &str) -> Result<()> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let field = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    match field {
+        "peers" => {
+            let mut peers = std::collections::BTreeSet::new();
+            for git_url in value
+                .split(',')
+                .map(|git_url| git_url.trim())
+                .filter(|git_url| !git_url.is_empty())
+            {
+                let git_url = crate::common::GitUrl::try_from(git_url)
+                    .map_err(|_| format_err!("Failed to parse peer git URL: {}", git_url))?;
+                peers.insert(git_url.to_string());
+            }
+            blocklist.peers = peers;
+            Ok(())
+        }
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
+
+pub fn get(blocklist: &Blocklist, name: &str) -> Result<String> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let field = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    match field {
+        "peers" => Ok(blocklist
+            .peers
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(",")),
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_normalizes_git_urls() -> Result<()> {
+        let mut blocklist = Blocklist::default();
+        set(
+            &mut blocklist,
+            "blocklist.peers",
+            "https://github.com/spammer/reviews.git, https://gitlab.com/spammer/reviews/",
+        )?;
+
+        let git_url = crate::common::GitUrl::try_from("https://github.com/spammer/reviews")?;
+        assert!(blocklist.is_blocked(&git_url));
+        let git_url = crate::common::GitUrl::try_from("https://gitlab.com/spammer/reviews")?;
+        assert!(blocklist.is_blocked(&git_url));
+        assert!(set(&mut blocklist, "blocklist.peers", "spammer").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unnormalized_entry_blocks_and_unblocks() -> Result<()> {
+        let mut blocklist = Blocklist {
+            peers: maplit::btreeset! {"https://github.com/spammer/reviews.git/".to_string()},
+        };
+        let git_url = crate::common::GitUrl::try_from("https://github.com/spammer/reviews")?;
+        assert!(blocklist.is_blocked(&git_url));
+        assert!(blocklist.remove(&git_url));
+        assert!(!blocklist.is_blocked(&git_url));
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{format_err, Result};
+
+#[derive(
+    Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize,
+)]
+pub struct Check {
+    /// Furthest version distance at which reviews of another package version are accepted.
+    #[serde(rename = "accept-version-distance", default)]
+    pub accept_version_distance: vouch_lib::extension::VersionDistance,
+
+    /// Review criteria which at least one counted review must certify for a dependency to pass.
+    #[serde(rename = "require-criteria", default)]
+    pub require_criteria: std::collections::BTreeSet<String>,
+
+    /// Reviews with a lower or unknown reviewer confidence are not counted.
+    #[serde(rename = "min-confidence", default)]
+    pub min_confidence: Option<crate::review::Confidence>,
+}
+
+fn get_regex() -> Result<regex::Regex> {
+    Ok(regex::Regex::new(r"check\.(.*)")?)
+}
+
+pub fn is_match(name: &str) -> Result<bool> {
+    Ok(get_regex()?.is_match(name))
+}
+
+pub fn set(check: &mut Check, name: &str, value: &str) -> Result<()> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let field = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    match field {
+        "accept-version-distance" => {
+            check.accept_version_distance = value.parse()?;
+            Ok(())
+        }
+        "require-criteria" => {
+            check.require_criteria = value
+                .split(',')
+                .map(|criterion| criterion.trim())
+                .filter(|criterion| !criterion.is_empty())
+                .map(|criterion| criterion.to_string())
+                .collect();
+            Ok(())
+        }
+        "min-confidence" => {
+            check.min_confidence = match value {
+                "" | "none" => None,
+                value => Some(value.parse()?),
+            };
+            Ok(())
+        }
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
+
+pub fn get(check: &Check, name: &str) -> Result<String> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let field = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    match field {
+        "accept-version-distance" => Ok(check.accept_version_distance.to_string()),
+        "require-criteria" => Ok(check
+            .require_criteria
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(",")),
+        "min-confidence" => Ok(check
+            .min_confidence
+            .map(|confidence| confidence.to_string())
+            .unwrap_or("none".to_string())),
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_use_defaults() -> Result<()> {
+        let result: Check = serde_yaml::from_str("require-criteria:\n  - safe-to-run\n")?;
+        let expected = Check {
+            require_criteria: maplit::btreeset! {"safe-to-run".to_string()},
+            ..Default::default()
+        };
+        assert_eq!(result, expected);
+        Ok(())
+    }
+}
@@ -7,19 +7,60 @@ use anyhow::{format_err, Result};
 pub struct Extensions {
     pub enabled: std::collections::BTreeMap<String, bool>,
     pub registries: std::collections::BTreeMap<String, String>,
+
+    /// Time limit in seconds for extension process commands. Zero disables the limit.
+    #[serde(
+        rename = "timeout-seconds",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout_seconds: Option<u64>,
+
+    /// Installation sources of extensions added by URL or name, keyed by extension name.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub sources: std::collections::BTreeMap<String, ExtensionSource>,
 }
 
+/// Extension installation source.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExtensionSource {
+    /// URL given when adding the extension: a GitHub repository or release archive URL.
+    pub url: url::Url,
+
+    /// Installed release archive URL.
+    #[serde(rename = "archive-url")]
+    pub archive_url: url::Url,
+}
+
+impl Extensions {
+    /// Returns the time limit for extension process commands. None if disabled.
+    pub fn get_timeout(&self) -> Option<std::time::Duration> {
+        match self.timeout_seconds {
+            None => Some(vouch_lib::extension::process::DEFAULT_TIMEOUT),
+            Some(0) => None,
+            Some(seconds) => Some(std::time::Duration::from_secs(seconds)),
+        }
+    }
+}
+
+static TIMEOUT_NAME: &str = "extensions.timeout-seconds";
+
 fn get_regex() -> Result<regex::Regex> {
     Ok(regex::Regex::new(r"extensions\.enabled\.(.*)")?)
 }
 
 pub fn is_match(name: &str) -> Result<bool> {
-    Ok(get_regex()?.is_match(name))
+    Ok(name == TIMEOUT_NAME || get_regex()?.is_match(name))
 }
 
 pub fn set(extensions: &mut Extensions, name: &str, value: &str) -> Result<()> {
     let name_error_message = format!("Unknown setting field name: {}", name);
 
+    if name == TIMEOUT_NAME {
+        extensions.timeout_seconds = Some(value.parse()?);
+        return Ok(());
+    }
+
     let captures = get_regex()?
         .captures(name)
         .ok_or(format_err!(name_error_message.clone()))?;
@@ -41,6 +82,14 @@ pub fn set(extensions: &mut Extensions, name: &str, value: &str) -> Result<()> {
 pub fn get(extensions: &Extensions, name: &str) -> Result<String> {
     let name_error_message = format!("Unknown setting field name: {}", name);
 
+    if name == TIMEOUT_NAME {
+        return Ok(extensions
+            .get_timeout()
+            .map(|timeout| timeout.as_secs())
+            .unwrap_or(0)
+            .to_string());
+    }
+
     let captures = get_regex()?
         .captures(name)
         .ok_or(format_err!(name_error_message.clone()))?;
@@ -1,10 +1,19 @@
 use anyhow::{format_err, Context, Result};
 
+mod blocklist;
+mod check;
 mod common;
 mod core;
 mod extensions;
+mod peers;
+mod quarantine;
+mod review_criteria;
 mod review_tool;
 
+pub use blocklist::Blocklist;
+pub use extensions::ExtensionSource;
+pub use peers::PeerOptions;
+
 #[derive(
     Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize,
 )]
@@ -15,6 +24,21 @@ pub struct Config {
     pub review_tool: review_tool::ReviewTool,
 
     pub extensions: extensions::Extensions,
+
+    #[serde(default)]
+    pub check: check::Check,
+
+    #[serde(rename = "review-criteria", default)]
+    pub review_criteria: review_criteria::ReviewCriteria,
+
+    #[serde(default)]
+    pub peers: peers::Peers,
+
+    #[serde(default)]
+    pub blocklist: blocklist::Blocklist,
+
+    #[serde(default)]
+    pub quarantine: quarantine::Quarantine,
 }
 
 impl Config {
@@ -51,12 +75,27 @@ impl Config {
     pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
         let name_error_message = format!("Unknown settings field: {}", name);
 
-        return if core::is_match(name)? {
+        // Peer aliases may contain other section names. Match peer options first.
+        return if peers::is_match(name)? {
+            Ok(peers::set(&mut self.peers, &name, &value)?)
+        } else if core::is_match(name)? {
             Ok(core::set(&mut self.core, &name, &value)?)
         } else if extensions::is_match(name)? {
             Ok(extensions::set(&mut self.extensions, &name, &value)?)
         } else if review_tool::is_match(name)? {
             Ok(review_tool::set(&mut self.review_tool, &name, &value)?)
+        } else if check::is_match(name)? {
+            Ok(check::set(&mut self.check, &name, &value)?)
+        } else if review_criteria::is_match(name)? {
+            Ok(review_criteria::set(
+                &mut self.review_criteria,
+                &name,
+                &value,
+            )?)
+        } else if blocklist::is_match(name)? {
+            Ok(blocklist::set(&mut self.blocklist, &name, &value)?)
+        } else if quarantine::is_match(name)? {
+            Ok(quarantine::set(&mut self.quarantine, &name, &value)?)
         } else {
             Err(format_err!(name_error_message.clone()))
         };
@@ -65,12 +104,22 @@ impl Config {
     pub fn get(&self, name: &str) -> Result<String> {
         let name_error_message = format!("Unknown settings field: {}", name);
 
-        return if core::is_match(name)? {
+        return if peers::is_match(name)? {
+            Ok(peers::get(&self.peers, &name)?)
+        } else if core::is_match(name)? {
             Ok(core::get(&self.core, &name)?)
         } else if extensions::is_match(name)? {
             Ok(extensions::get(&self.extensions, &name)?)
         } else if review_tool::is_match(name)? {
             Ok(review_tool::get(&self.review_tool, &name)?)
+        } else if check::is_match(name)? {
+            Ok(check::get(&self.check, &name)?)
+        } else if review_criteria::is_match(name)? {
+            Ok(review_criteria::get(&self.review_criteria, &name)?)
+        } else if blocklist::is_match(name)? {
+            Ok(blocklist::get(&self.blocklist, &name)?)
+        } else if quarantine::is_match(name)? {
+            Ok(quarantine::get(&self.quarantine, &name)?)
         } else {
             Err(format_err!(name_error_message.clone()))
         };
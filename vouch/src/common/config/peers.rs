@@ -0,0 +1,186 @@
+use anyhow::{format_err, Result};
+
+/// Options for directly followed peers, keyed by peer alias.
+#[derive(
+    Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+pub struct Peers {
+    pub options: std::collections::BTreeMap<String, PeerOptions>,
+}
+
+impl Peers {
+    /// Returns the options for the given peer alias. Defaults if none are set.
+    pub fn get_options(&self, alias: &str) -> PeerOptions {
+        self.options.get(alias).cloned().unwrap_or_default()
+    }
+}
+
+/// Limits which parts of a followed peer's subtree are imported.
+#[derive(
+    Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize,
+)]
+pub struct PeerOptions {
+    /// Maximum depth of imported sub-peers. Zero imports the followed peer's own reviews only.
+    #[serde(rename = "max-depth", default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+
+    /// If non-empty, only sub-peers with these git URLs are imported.
+    #[serde(
+        rename = "allow-peers",
+        default,
+        skip_serializing_if = "std::collections::BTreeSet::is_empty"
+    )]
+    pub allow_peers: std::collections::BTreeSet<String>,
+
+    /// Sub-peers with these git URLs are not imported.
+    #[serde(
+        rename = "deny-peers",
+        default,
+        skip_serializing_if = "std::collections::BTreeSet::is_empty"
+    )]
+    pub deny_peers: std::collections::BTreeSet<String>,
+
+    /// If non-empty, only reviews of packages from these registries are imported.
+    #[serde(
+        rename = "allow-registries",
+        default,
+        skip_serializing_if = "std::collections::BTreeSet::is_empty"
+    )]
+    pub allow_registries: std::collections::BTreeSet<String>,
+
+    /// Reviews of packages from these registries are not imported.
+    #[serde(
+        rename = "deny-registries",
+        default,
+        skip_serializing_if = "std::collections::BTreeSet::is_empty"
+    )]
+    pub deny_registries: std::collections::BTreeSet<String>,
+}
+
+impl PeerOptions {
+    /// Returns true if a sub-peer at the given depth below the followed peer should be imported.
+    pub fn is_peer_allowed(&self, git_url: &crate::common::GitUrl, depth: usize) -> bool {
+        if let Some(max_depth) = self.max_depth {
+            if depth > max_depth {
+                return false;
+            }
+        }
+        self.is_peer_git_url_allowed(&git_url)
+    }
+
+    /// Returns true if the sub-peer git URL passes the allow and deny lists.
+    pub fn is_peer_git_url_allowed(&self, git_url: &crate::common::GitUrl) -> bool {
+        let git_url = git_url.as_str();
+        if self.deny_peers.contains(git_url) {
+            return false;
+        }
+        self.allow_peers.is_empty() || self.allow_peers.contains(git_url)
+    }
+
+    /// Returns true if a review of a package from the given registries should be imported.
+    pub fn is_registries_allowed<'a>(
+        &self,
+        registry_host_names: impl IntoIterator<Item = &'a str>,
+    ) -> bool {
+        let registry_host_names = registry_host_names.into_iter().collect::<Vec<_>>();
+        if registry_host_names
+            .iter()
+            .any(|host_name| self.deny_registries.contains(*host_name))
+        {
+            return false;
+        }
+        self.allow_registries.is_empty()
+            || registry_host_names
+                .iter()
+                .any(|host_name| self.allow_registries.contains(*host_name))
+    }
+}
+
+fn get_regex() -> Result<regex::Regex> {
+    Ok(regex::Regex::new(r"^peers\.(.+)\.([^.]+)$")?)
+}
+
+pub fn is_match(name: &str) -> Result<bool> {
+    Ok(get_regex()?.is_match(name))
+}
+
+fn parse_list(value: &str) -> std::collections::BTreeSet<String> {
+    value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| item.to_string())
+        .collect()
+}
+
+/// Set a peer option. Peers without any remaining options are removed.
+pub fn set(peers: &mut Peers, name: &str, value: &str) -> Result<()> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let alias = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+    let field = captures
+        .get(2)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    let mut options = peers.get_options(alias);
+    match field {
+        "max-depth" => {
+            options.max_depth = match value {
+                "" | "none" => None,
+                value => Some(value.parse()?),
+            };
+        }
+        "allow-peers" => options.allow_peers = parse_list(value),
+        "deny-peers" => options.deny_peers = parse_list(value),
+        "allow-registries" => options.allow_registries = parse_list(value),
+        "deny-registries" => options.deny_registries = parse_list(value),
+        _ => return Err(format_err!(name_error_message.clone())),
+    }
+
+    if options == PeerOptions::default() {
+        peers.options.remove(alias);
+    } else {
+        peers.options.insert(alias.to_string(), options);
+    }
+    Ok(())
+}
+
+pub fn get(peers: &Peers, name: &str) -> Result<String> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let alias = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+    let field = captures
+        .get(2)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    let options = peers.get_options(alias);
+    let join = |items: &std::collections::BTreeSet<String>| {
+        items.iter().cloned().collect::<Vec<_>>().join(",")
+    };
+    match field {
+        "max-depth" => Ok(options
+            .max_depth
+            .map(|max_depth| max_depth.to_string())
+            .unwrap_or("none".to_string())),
+        "allow-peers" => Ok(join(&options.allow_peers)),
+        "deny-peers" => Ok(join(&options.deny_peers)),
+        "allow-registries" => Ok(join(&options.allow_registries)),
+        "deny-registries" => Ok(join(&options.deny_registries)),
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
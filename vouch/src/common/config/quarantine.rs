@@ -0,0 +1,77 @@
+use crate::common::config::common;
+use anyhow::{format_err, Result};
+
+/// Keeps reviews by indirectly followed peers out of `check` until the peer is approved.
+///
+/// Directly followed peers are never quarantined.
+#[derive(
+    Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize,
+)]
+pub struct Quarantine {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Approved peer git URLs.
+    #[serde(rename = "approved-peers", default)]
+    pub approved_peers: std::collections::BTreeSet<String>,
+}
+
+fn get_regex() -> Result<regex::Regex> {
+    Ok(regex::Regex::new(r"^quarantine\.(.*)")?)
+}
+
+pub fn is_match(name: &str) -> Result<bool> {
+    Ok(get_regex()?.is_match(name))
+}
+
+pub fn set(quarantine: &mut Quarantine, name: &str, value: &str) -> Result<()> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let field = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    match field {
+        "enabled" => {
+            quarantine.enabled = common::bool_from_string(&value)?;
+            Ok(())
+        }
+        "approved-peers" => {
+            quarantine.approved_peers = value
+                .split(',')
+                .map(|git_url| git_url.trim())
+                .filter(|git_url| !git_url.is_empty())
+                .map(|git_url| git_url.to_string())
+                .collect();
+            Ok(())
+        }
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
+
+pub fn get(quarantine: &Quarantine, name: &str) -> Result<String> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let field = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    match field {
+        "enabled" => Ok(quarantine.enabled.to_string()),
+        "approved-peers" => Ok(quarantine
+            .approved_peers
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(",")),
+        _ => Err(format_err!(name_error_message.clone())),
+    }
+}
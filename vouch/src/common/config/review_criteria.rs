@@ -0,0 +1,103 @@
+use anyhow::{format_err, Result};
+
+/// Criteria which reviewers may certify a package as meeting.
+///
+/// Maps criterion names to descriptions.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ReviewCriteria {
+    pub descriptions: std::collections::BTreeMap<String, String>,
+}
+
+impl Default for ReviewCriteria {
+    fn default() -> Self {
+        Self {
+            descriptions: maplit::btreemap! {
+                "safe-to-run".to_string() =>
+                    "Can be compiled, run and tested on a local workstation or in controlled \
+                    automation without surprising consequences."
+                        .to_string(),
+                "safe-to-deploy".to_string() =>
+                    "Can be relied upon in production on untrusted input. Does not hold \
+                    exploitable vulnerabilities or malicious code."
+                        .to_string(),
+            },
+        }
+    }
+}
+
+impl ReviewCriteria {
+    /// Returns an error if any of the given criteria names are not configured.
+    pub fn check_known<'a>(&self, names: impl IntoIterator<Item = &'a String>) -> Result<()> {
+        for name in names {
+            if !self.descriptions.contains_key(name) {
+                return Err(format_err!(
+                    "Unknown review criterion: {name}. Known criteria: {known}",
+                    name = name,
+                    known = self
+                        .descriptions
+                        .keys()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_regex() -> Result<regex::Regex> {
+    Ok(regex::Regex::new(r"review-criteria\.(.*)")?)
+}
+
+pub fn is_match(name: &str) -> Result<bool> {
+    Ok(get_regex()?.is_match(name))
+}
+
+/// Set a criterion description. An empty description removes the criterion.
+pub fn set(review_criteria: &mut ReviewCriteria, name: &str, value: &str) -> Result<()> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let criterion_name = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    if criterion_name.is_empty() || criterion_name.contains(char::is_whitespace) {
+        return Err(format_err!(
+            "Review criterion name must be non-empty and must not contain whitespace: {}",
+            criterion_name
+        ));
+    }
+
+    if value.is_empty() {
+        review_criteria.descriptions.remove(criterion_name);
+    } else {
+        review_criteria
+            .descriptions
+            .insert(criterion_name.to_string(), value.to_string());
+    }
+    Ok(())
+}
+
+pub fn get(review_criteria: &ReviewCriteria, name: &str) -> Result<String> {
+    let name_error_message = format!("Unknown setting field name: {}", name);
+
+    let captures = get_regex()?
+        .captures(name)
+        .ok_or(format_err!(name_error_message.clone()))?;
+    let criterion_name = captures
+        .get(1)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .as_str();
+
+    Ok(review_criteria
+        .descriptions
+        .get(criterion_name)
+        .ok_or(format_err!(name_error_message.clone()))?
+        .clone())
+}
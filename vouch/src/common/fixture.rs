@@ -0,0 +1,129 @@
+//! Test fixtures: a local stand-in HTTP server for registries and the GitHub releases API.
+use anyhow::{format_err, Result};
+use std::io::{Read, Write};
+
+/// Minimal HTTP server which serves fixed responses from a background thread.
+///
+/// Responds to GET requests with the body registered for the request path, otherwise 404.
+/// The server stops when dropped.
+pub struct Server {
+    address: std::net::SocketAddr,
+    is_stopped: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Server {
+    pub fn new(routes: std::collections::BTreeMap<String, Vec<u8>>) -> Result<Self> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let is_stopped = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        let thread_is_stopped = is_stopped.clone();
+        let thread = std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_is_stopped.load(std::sync::atomic::Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    if let Err(error) = respond(stream, &routes) {
+                        log::debug!("Fixture server failed to respond: {}", error);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            address,
+            is_stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the server URL for the given absolute path.
+    pub fn url(&self, path: &str) -> Result<url::Url> {
+        Ok(url::Url::parse(&format!(
+            "http://{address}{path}",
+            address = self.address,
+            path = path
+        ))?)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.is_stopped
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        // Unblock the listener so that the thread can observe the stop flag.
+        let _ = std::net::TcpStream::connect(self.address);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn respond(
+    mut stream: std::net::TcpStream,
+    routes: &std::collections::BTreeMap<String, Vec<u8>>,
+) -> Result<()> {
+    // Read request head. Fixture requests do not carry a body.
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let count = stream.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..count]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let path = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .ok_or(format_err!("Failed to parse request line: {}", request))?;
+
+    let (status, body) = match routes.get(path) {
+        Some(body) => ("200 OK", body.as_slice()),
+        None => ("404 Not Found", &b""[..]),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n",
+        status = status,
+        length = body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Returns a tar.gz archive containing the given files as (path, content, mode) entries.
+pub fn get_tar_gz_archive(files: &[(&str, &[u8], u32)]) -> Result<Vec<u8>> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (path, content, mode) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(*mode);
+        header.set_cksum();
+        builder.append_data(&mut header, path, *content)?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+/// Returns a GitHub releases API response with a single release holding the given assets.
+///
+/// Assets are given as (name, download URL) pairs.
+pub fn get_github_releases(assets: &[(&str, &url::Url)]) -> Result<Vec<u8>> {
+    let assets: Vec<_> = assets
+        .iter()
+        .map(|(name, url)| {
+            serde_json::json!({
+                "name": name,
+                "browser_download_url": url.as_str(),
+            })
+        })
+        .collect();
+    let releases = serde_json::json!([{ "tag_name": "v0.1.0", "assets": assets }]);
+    Ok(serde_json::to_vec(&releases)?)
+}
@@ -70,6 +70,29 @@ mod tests {
         assert!(result == expected);
         Ok(())
     }
+
+    #[test]
+    fn test_download_writes_response_body() -> Result<()> {
+        let server = crate::common::fixture::Server::new(maplit::btreemap! {
+            "/d3-4.10.0.tar.gz".to_string() => b"archive content".to_vec(),
+        })?;
+        let tmp_dir = tempdir::TempDir::new("vouch_test_download")?;
+        let destination_path = tmp_dir.path().join("archive.tar.gz");
+
+        download(&server.url("/d3-4.10.0.tar.gz")?, &destination_path)?;
+        assert_eq!(std::fs::read(&destination_path)?, b"archive content");
+        Ok(())
+    }
+
+    #[test]
+    fn test_download_errors_for_missing_archive() -> Result<()> {
+        let server = crate::common::fixture::Server::new(std::collections::BTreeMap::new())?;
+        let tmp_dir = tempdir::TempDir::new("vouch_test_download")?;
+        let destination_path = tmp_dir.path().join("archive.tar.gz");
+
+        assert!(download(&server.url("/missing.tar.gz")?, &destination_path).is_err());
+        Ok(())
+    }
 }
 
 pub fn extract(
@@ -228,7 +251,16 @@ pub fn download(target_url: &url::Url, destination_path: &std::path::PathBuf) ->
         destination_path.display()
     );
 
-    let response = reqwest::blocking::get(target_url.clone())?;
+    if target_url.scheme() == "file" {
+        let source_path = target_url
+            .to_file_path()
+            .map_err(|_| format_err!("Failed to parse file URL path: {}", target_url))?;
+        std::fs::copy(&source_path, &destination_path)?;
+        log::debug!("Finished copying local archive.");
+        return Ok(());
+    }
+
+    let response = reqwest::blocking::get(target_url.clone())?.error_for_status()?;
     let mut file = std::fs::File::create(&destination_path)?;
     let content = response.bytes()?;
     file.write_all(&content)?;
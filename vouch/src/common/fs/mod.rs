@@ -27,6 +27,9 @@ pub fn ensure_extensions_bin_directory() -> Result<Option<std::path::PathBuf>> {
 /// Does not create the directory.
 /// Returns None if home directory does not exist.
 pub fn get_extensions_default_directory() -> Option<std::path::PathBuf> {
+    if let Some(vouch_home) = get_vouch_home() {
+        return Some(vouch_home.join("bin"));
+    }
     let extensions_directory_name = ".vouch_extensions";
 
     match dirs::home_dir() {
@@ -42,6 +45,17 @@ pub fn get_extensions_default_directory() -> Option<std::path::PathBuf> {
     }
 }
 
+/// Returns the directory given by the VOUCH_HOME environment variable, if set.
+///
+/// When set, config, data and extension binaries are stored under this directory instead of
+/// the platform user directories.
+pub fn get_vouch_home() -> Option<std::path::PathBuf> {
+    match std::env::var_os("VOUCH_HOME") {
+        Some(vouch_home) if !vouch_home.is_empty() => Some(std::path::PathBuf::from(vouch_home)),
+        _ => None,
+    }
+}
+
 #[cfg(windows)]
 fn set_directory_hidden_windows(directory: &std::path::PathBuf) {
     // TODO: Hide directory on Windows.
@@ -61,12 +75,17 @@ pub struct ConfigPaths {
 
 impl ConfigPaths {
     pub fn new() -> Result<Self> {
-        let user_directories = directories::ProjectDirs::from("", "", "vouch").ok_or(
-            format_err!("Failed to obtain a handle on the local user directory."),
-        )?;
-        let root_directory = user_directories.config_dir();
+        let root_directory = match get_vouch_home() {
+            Some(vouch_home) => vouch_home.join("config"),
+            None => directories::ProjectDirs::from("", "", "vouch")
+                .ok_or(format_err!(
+                    "Failed to obtain a handle on the local user directory."
+                ))?
+                .config_dir()
+                .to_path_buf(),
+        };
         Ok(Self {
-            root_directory: root_directory.into(),
+            root_directory: root_directory.clone(),
             config_file: root_directory.join("config.yaml"),
             extensions_directory: root_directory.join("extensions"),
         })
@@ -103,11 +122,16 @@ impl DataPaths {
     }
 
     pub fn new() -> Result<Self> {
-        let user_directories = directories::ProjectDirs::from("", "", "vouch").ok_or(
-            format_err!("Failed to obtain a handle on the local user directory."),
-        )?;
-        let root_directory = user_directories.data_local_dir();
-        Self::from_root_directory(&root_directory.into())
+        let root_directory = match get_vouch_home() {
+            Some(vouch_home) => vouch_home.join("data"),
+            None => directories::ProjectDirs::from("", "", "vouch")
+                .ok_or(format_err!(
+                    "Failed to obtain a handle on the local user directory."
+                ))?
+                .data_local_dir()
+                .to_path_buf(),
+        };
+        Self::from_root_directory(&root_directory)
     }
 
     /// Returns true if the given absolute path is protected from deletion, otherwise false.
@@ -120,12 +144,15 @@ impl DataPaths {
     }
 }
 
-pub fn git(args: Vec<&str>, working_directory: &std::path::PathBuf) -> Result<()> {
-    std::process::Command::new("git")
+/// Run a git command in the given directory. Returns the command's exit status.
+pub fn git(
+    args: Vec<&str>,
+    working_directory: &std::path::PathBuf,
+) -> Result<std::process::ExitStatus> {
+    Ok(std::process::Command::new("git")
         .args(args)
         .current_dir(working_directory)
-        .status()?;
-    Ok(())
+        .status()?)
 }
 
 /// Remove empty directories along relative path.
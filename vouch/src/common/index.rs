@@ -68,3 +68,35 @@ pub fn get_ids_where_field<'a>(ids: &Option<&'a Vec<crate::common::index::ID>>)
         None => "true".to_string(),
     }
 }
+
+/// Returns an SQL clause which matches a column value against any of the given values.
+///
+/// Values are bound using named parameters which share the given parameter prefix.
+/// The clause matches any row if values are not given.
+pub fn get_in_clause(
+    column: &str,
+    parameter_prefix: &str,
+    values: &Option<std::collections::BTreeSet<&str>>,
+) -> (String, Vec<(String, String)>) {
+    match values {
+        Some(values) => {
+            let parameters: Vec<(String, String)> = values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    (
+                        format!(":{}_{}", parameter_prefix, index),
+                        value.to_string(),
+                    )
+                })
+                .collect();
+            let parameter_names = parameters
+                .iter()
+                .map(|(name, _value)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            (format!("{} IN ({})", column, parameter_names), parameters)
+        }
+        None => ("true".to_string(), Vec::new()),
+    }
+}
@@ -9,6 +9,8 @@ use anyhow::Result;
 use std::convert::TryFrom;
 
 pub mod config;
+#[cfg(test)]
+pub mod fixture;
 pub mod fs;
 pub mod index;
 
@@ -58,13 +60,32 @@ impl GitUrl {
     pub fn url(&self) -> &url::Url {
         &self.0
     }
+
+    /// Returns true if the URL refers to a repository on the local file system.
+    pub fn is_local(&self) -> bool {
+        self.0.scheme() == "file"
+    }
 }
 
 impl std::convert::TryFrom<&str> for GitUrl {
     type Error = url::ParseError;
 
+    /// Parse a git URL. Local file system paths are converted to file URLs.
+    ///
+    /// Trailing slashes and the `.git` suffix are dropped from remote URLs only. Local bare
+    /// repositories are commonly named with the suffix and can not be found without it.
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let value = remove_suffix(value, ".git");
+        if is_local_path(value) {
+            return Ok(Self {
+                0: get_file_url(value)?,
+            });
+        }
+        let url = url::Url::parse(value)?;
+        if url.scheme() == "file" {
+            return Ok(Self { 0: url });
+        }
+
+        let value = remove_suffix(value.trim_end_matches('/'), ".git");
         Ok(Self {
             0: url::Url::parse(value)?,
         })
@@ -75,13 +96,41 @@ impl std::convert::TryFrom<&String> for GitUrl {
     type Error = url::ParseError;
 
     fn try_from(value: &String) -> Result<Self, Self::Error> {
-        let value = remove_suffix(value, ".git");
-        Ok(Self {
-            0: url::Url::parse(value)?,
-        })
+        Self::try_from(value.as_str())
     }
 }
 
+/// Returns true if the value is a file system path rather than a URL.
+///
+/// Paths must be absolute, explicitly relative (`./`, `../`), home relative (`~`) or start with a
+/// drive letter. Other values are parsed as URLs.
+fn is_local_path(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let is_windows_drive_path = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+    is_windows_drive_path
+        || value.starts_with('/')
+        || value.starts_with("./")
+        || value.starts_with("../")
+        || value.starts_with('~')
+}
+
+/// Convert a local file system path into an absolute file URL.
+fn get_file_url(path: &str) -> Result<url::Url, url::ParseError> {
+    let path = std::path::PathBuf::from(shellexpand::tilde(path).as_ref());
+    let path = if path.is_absolute() {
+        path
+    } else {
+        std::env::current_dir()
+            .map_err(|_| url::ParseError::RelativeUrlWithoutBase)?
+            .join(path)
+    };
+    let path = path.canonicalize().unwrap_or(path);
+    url::Url::from_file_path(&path).map_err(|_| url::ParseError::RelativeUrlWithoutBase)
+}
+
 fn remove_suffix<'a>(s: &'a str, p: &str) -> &'a str {
     if s.ends_with(p) {
         &s[..s.len() - p.len()]
@@ -136,3 +185,94 @@ pub trait HashSansId {
     /// Compute hash without ID field.
     fn hash_sans_id<H: std::hash::Hasher>(&self, state: &mut H);
 }
+
+/// Content-addressed identifier which is stable across indexes, peers and Vouch versions.
+///
+/// Unlike index IDs, content hashes can be used to refer to the same item in other peers' indexes.
+pub trait ContentHash {
+    /// Returns the blake3 hash of the canonical serialization of the content. Excludes index IDs.
+    fn content_hash(&self) -> String;
+}
+
+/// Returns the blake3 hex digest of the canonical JSON serialization of the given content.
+///
+/// Object keys are serialized in sorted order without whitespace.
+pub fn get_content_hash(content: &serde_json::Value) -> String {
+    let mut canonical = Vec::new();
+    write_canonical_json(&content, &mut canonical);
+    blake3::hash(&canonical).to_hex().as_str().to_string()
+}
+
+fn write_canonical_json(value: &serde_json::Value, output: &mut Vec<u8>) {
+    match value {
+        serde_json::Value::Array(values) => {
+            output.push(b'[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push(b',');
+                }
+                write_canonical_json(&value, output);
+            }
+            output.push(b']');
+        }
+        serde_json::Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_by(|(key, _), (other_key, _)| key.cmp(other_key));
+
+            output.push(b'{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    output.push(b',');
+                }
+                output.extend(
+                    serde_json::Value::String(key.clone())
+                        .to_string()
+                        .as_bytes(),
+                );
+                output.push(b':');
+                write_canonical_json(&value, output);
+            }
+            output.push(b'}');
+        }
+        value => output.extend(value.to_string().as_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_git_url_remote_suffix_removed() -> anyhow::Result<()> {
+        let result = GitUrl::try_from("https://github.com/user/reviews.git")?;
+        assert_eq!(result.as_str(), "https://github.com/user/reviews");
+        assert!(!result.is_local());
+
+        let result = GitUrl::try_from("https://github.com/user/reviews.git/")?;
+        assert_eq!(result.as_str(), "https://github.com/user/reviews");
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_url_from_local_path() -> anyhow::Result<()> {
+        let tmp_dir = tempdir::TempDir::new("vouch_test_git_url")?;
+        let repository_path = tmp_dir.path().canonicalize()?.join("reviews.git");
+        std::fs::create_dir(&repository_path)?;
+
+        let result = GitUrl::try_from(repository_path.to_str().unwrap())?;
+        let expected = url::Url::from_file_path(&repository_path).unwrap();
+        assert_eq!(result.url(), &expected);
+        assert!(result.is_local());
+
+        let result = GitUrl::try_from(expected.as_str())?;
+        assert_eq!(result.url(), &expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_url_bare_name_rejected() {
+        let result = GitUrl::try_from("reviews");
+        assert_eq!(result, Err(url::ParseError::RelativeUrlWithoutBase));
+    }
+}
@@ -3,11 +3,18 @@ use std::io::Read;
 
 use crate::common;
 
+static API_URL: &str = "https://api.github.com";
+
 pub fn get_archive_url(repo_url: &url::Url) -> Result<Option<url::Url>> {
+    get_release_archive_url(&url::Url::parse(API_URL)?, &repo_url)
+}
+
+/// Returns the release archive URL for the current platform using the given GitHub API.
+fn get_release_archive_url(api_url: &url::Url, repo_url: &url::Url) -> Result<Option<url::Url>> {
     let platform = get_platform()?;
     log::debug!("Identified target platform: {}", platform);
 
-    let releases = get_releases(&repo_url)?;
+    let releases = get_releases(&api_url, &repo_url)?;
     if releases.is_empty() {
         log::debug!("Failed to find any releases corresponding to repository URL.");
     } else {
@@ -34,20 +41,18 @@ pub fn get_archive_url(repo_url: &url::Url) -> Result<Option<url::Url>> {
 }
 
 /// Get releases given a repository URL such as: https://github.com/vouch-dev/vouch-py
-fn get_releases(repo_url: &url::Url) -> Result<Vec<serde_json::Value>> {
-    let releases_url = url::Url::parse(
-        format!(
-            "https://api.github.com/repos{path}/releases",
-            path = repo_url.path()
-        )
-        .as_str(),
-    )?;
+fn get_releases(api_url: &url::Url, repo_url: &url::Url) -> Result<Vec<serde_json::Value>> {
+    let releases_url =
+        api_url.join(format!("repos{path}/releases", path = repo_url.path()).as_str())?;
     log::debug!("Using releases URL: {}", releases_url);
 
     let client = reqwest::blocking::Client::builder()
         .user_agent(common::HTTP_USER_AGENT)
         .build()?;
-    let mut result = client.get(&releases_url.to_string()).send()?;
+    let mut result = client
+        .get(&releases_url.to_string())
+        .send()?
+        .error_for_status()?;
     let mut body = String::new();
     result.read_to_string(&mut body)?;
     let releases: serde_json::Value =
@@ -67,3 +72,36 @@ fn get_platform() -> Result<String> {
     }
     .to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::fixture;
+
+    #[test]
+    fn test_get_release_archive_url_selects_platform_asset() -> Result<()> {
+        let platform = get_platform()?;
+        let download_url = url::Url::parse("https://example.com/vouch-fixture.tar.gz")?;
+        let other_url = url::Url::parse("https://example.com/vouch-fixture-other.tar.gz")?;
+        let releases = fixture::get_github_releases(&[
+            ("vouch-fixture-other-platform.tar.gz", &other_url),
+            (&format!("vouch-fixture-{}.tar.gz", platform), &download_url),
+        ])?;
+        let server = fixture::Server::new(maplit::btreemap! {
+            "/repos/vouch-dev/vouch-fixture/releases".to_string() => releases,
+        })?;
+
+        let repo_url = url::Url::parse("https://github.com/vouch-dev/vouch-fixture")?;
+        let result = get_release_archive_url(&server.url("/")?, &repo_url)?;
+        assert_eq!(result, Some(download_url));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_release_archive_url_errors_for_missing_repository() -> Result<()> {
+        let server = fixture::Server::new(std::collections::BTreeMap::new())?;
+        let repo_url = url::Url::parse("https://github.com/vouch-dev/missing")?;
+        assert!(get_release_archive_url(&server.url("/")?, &repo_url).is_err());
+        Ok(())
+    }
+}
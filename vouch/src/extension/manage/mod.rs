@@ -5,10 +5,13 @@ use crate::common::config::Config;
 use crate::extension::{common, process};
 mod github;
 
+/// Installs the extension binary from the given release archive or GitHub repository URL.
+///
+/// Returns the extension name and the installed release archive URL.
 pub fn add_from_url(
     url: &url::Url,
     extensions_bin_directory: &std::path::PathBuf,
-) -> Result<String> {
+) -> Result<(String, url::Url)> {
     let archive_url = if is_supported_archive_url(&url)? {
         url.clone()
     } else {
@@ -22,7 +25,51 @@ pub fn add_from_url(
         }
     };
     log::info!("Using archive URL: {}", archive_url);
+    let extension_name = install_archive(&archive_url, &extensions_bin_directory)?;
+    Ok((extension_name, archive_url))
+}
+
+/// Reinstalls a process extension in place if its GitHub repository has a newer release.
+///
+/// The latest release is assumed to be newer if the installed archive URL is unknown.
+/// Returns the installed release archive URL if the extension was updated.
+pub fn update(
+    name: &str,
+    url: &url::Url,
+    installed_archive_url: Option<&url::Url>,
+) -> Result<Option<url::Url>> {
+    if is_supported_archive_url(&url)? {
+        return Err(format_err!(
+            "Extension {} was added from a release archive URL. \
+            Add the extension using its GitHub repository URL to enable updates.",
+            name
+        ));
+    }
+    let archive_url = get_archive_url(&url)?.ok_or(format_err!(
+        "Failed to obtain suitable release archive URL: {}",
+        url
+    ))?;
+    if installed_archive_url == Some(&archive_url) {
+        return Ok(None);
+    }
+
+    let extension_paths = process::get_extension_paths()?;
+    let bin_path = extension_paths
+        .get(name)
+        .ok_or(format_err!("Failed to find process extension: {}", name))?;
+    let bin_directory = bin_path.parent().ok_or(format_err!(
+        "Failed to find extension binary directory: {}",
+        bin_path.display()
+    ))?;
+    install_archive(&archive_url, &bin_directory.to_path_buf())?;
+    Ok(Some(archive_url))
+}
 
+/// Downloads the release archive and copies the extension binary into the given directory.
+fn install_archive(
+    archive_url: &url::Url,
+    extensions_bin_directory: &std::path::PathBuf,
+) -> Result<String> {
     let archive_type = crate::common::fs::archive::ArchiveType::try_from(
         &std::path::PathBuf::from(archive_url.path()),
     )?;
@@ -155,13 +202,51 @@ mod tests {
             Ok(())
         }
     }
+
+    #[test]
+    fn test_add_from_url_installs_archive_binary() -> Result<()> {
+        let archive = crate::common::fixture::get_tar_gz_archive(&[
+            ("README.md", b"Fixture extension.", 0o644),
+            ("vouch-fixture", b"#!/bin/sh\n", 0o644),
+        ])?;
+        let server = crate::common::fixture::Server::new(maplit::btreemap! {
+            "/releases/vouch-fixture.tar.gz".to_string() => archive,
+        })?;
+        let bin_directory = tempdir::TempDir::new("vouch_test_extensions_bin")?;
+
+        let archive_url = server.url("/releases/vouch-fixture.tar.gz")?;
+        let (name, installed_archive_url) =
+            add_from_url(&archive_url, &bin_directory.path().to_path_buf())?;
+        assert_eq!(name, "fixture");
+        assert_eq!(installed_archive_url, archive_url);
+
+        let bin_path = bin_directory.path().join("vouch-fixture");
+        assert_eq!(std::fs::read(&bin_path)?, b"#!/bin/sh\n");
+        #[cfg(target_family = "unix")]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                std::fs::metadata(&bin_path)?.permissions().mode() & 0o777,
+                0o755
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_rejects_archive_url_source() -> Result<()> {
+        let url = url::Url::parse("https://example.com/releases/vouch-fixture.tar.gz")?;
+        let error = update("fixture", &url, Some(&url)).unwrap_err();
+        assert!(error.to_string().contains("GitHub repository URL"));
+        Ok(())
+    }
 }
 
 /// Update config with discoverable extensions.
 pub fn update_config(config: &mut Config) -> Result<()> {
     log::debug!("Discover extensions and update config.");
 
-    let extensions = process::get_all()?;
+    let extensions = process::get_all(&config)?;
     let extension_name_map: std::collections::BTreeMap<_, _> = extensions
         .iter()
         .map(|extension| (extension.name(), extension))
@@ -205,12 +290,80 @@ pub fn update_config(config: &mut Config) -> Result<()> {
         }
     }
 
-    if !stale_names.is_empty() || !new_names.is_empty() {
+    // Update registries map for extensions which add or drop registries on upgrade.
+    let mut is_registries_changed = false;
+    for (name, extension) in &extension_name_map {
+        let registries: std::collections::BTreeSet<_> =
+            extension.registries().into_iter().collect();
+        for registry in &registries {
+            if !config.extensions.registries.contains_key(registry) {
+                config
+                    .extensions
+                    .registries
+                    .insert(registry.clone(), name.clone());
+                is_registries_changed = true;
+            }
+        }
+
+        let dropped_registries: Vec<_> = config
+            .extensions
+            .registries
+            .iter()
+            .filter(|(registry, extension_name)| {
+                *extension_name == name && !registries.contains(*registry)
+            })
+            .map(|(registry, _extension_name)| registry.clone())
+            .collect();
+        for registry in dropped_registries {
+            config.extensions.registries.remove(&registry);
+            is_registries_changed = true;
+        }
+    }
+
+    if !stale_names.is_empty() || !new_names.is_empty() || is_registries_changed {
         config.dump()?;
     }
     Ok(())
 }
 
+/// Returns all known extensions with process binary paths. Built-in extensions have no path.
+pub fn get_all_with_paths(config: &Config) -> Result<Vec<process::ExtensionWithPath>> {
+    process::get_all_with_paths(&config)
+}
+
+/// Returns the names of installed process extensions.
+pub fn get_process_names() -> Result<std::collections::BTreeSet<String>> {
+    Ok(process::get_extension_paths()?
+        .into_iter()
+        .map(|(name, _path)| name)
+        .collect())
+}
+
+/// Reloads static data for process extensions, ignoring cached data.
+///
+/// Refreshes all process extensions if no name is given. Returns refreshed extension names.
+pub fn refresh(name: &Option<String>, config: &Config) -> Result<Vec<String>> {
+    let mut extension_paths: Vec<_> = process::get_extension_paths()?.into_iter().collect();
+    extension_paths.sort();
+    if let Some(name) = name {
+        extension_paths.retain(|(extension_name, _path)| extension_name == name);
+        if extension_paths.is_empty() {
+            return Err(format_err!("Failed to find process extension: {}", name));
+        }
+    }
+
+    let mut names = Vec::new();
+    for (name, path) in extension_paths {
+        vouch_lib::extension::process::ProcessExtension::refresh(
+            &path,
+            &common::get_config_path(&name)?,
+            config.extensions.get_timeout(),
+        )?;
+        names.push(name);
+    }
+    Ok(names)
+}
+
 /// Enable extension.
 pub fn enable(name: &str, config: &mut Config) -> Result<()> {
     if let Some(enabled_status) = config.extensions.enabled.get_mut(&name.to_string()) {
@@ -263,6 +416,9 @@ pub fn remove(name: &str) -> Result<()> {
     }
 
     update_config(&mut config)?;
+    if config.extensions.sources.remove(name).is_some() {
+        config.dump()?;
+    }
     Ok(())
 }
 
@@ -277,7 +433,7 @@ pub fn get_enabled(
     config: &Config,
 ) -> Result<Vec<Box<dyn vouch_lib::extension::Extension>>> {
     log::debug!("Identifying enabled extensions.");
-    let extensions = process::get_all()?
+    let extensions = process::get_all(&config)?
         .into_iter()
         .filter(|extension| {
             *config
@@ -42,19 +42,26 @@ fn select_search_result<'a>(
         &'a Box<dyn vouch_lib::extension::Extension>,
     )>,
 ) -> Result<Vec<vouch_lib::extension::RegistryPackageMetadata>> {
-    let mut selection = Err(format_err!(
-        "Extensions have failed to find package in package registries."
-    ));
+    let mut selection = None;
     let mut ok_extension_names = Vec::<_>::new();
+    let mut error_messages = Vec::<_>::new();
 
     for (search_result, extension) in extensions_search_results.into_iter() {
-        if search_result.is_err() {
-            continue;
+        match search_result {
+            Ok(search_result) => {
+                ok_extension_names.push(extension.name());
+                selection = Some(search_result);
+            }
+            Err(error) => {
+                log::debug!("Extension {} error: {}", extension.name(), error);
+                error_messages.push(format!("{}: {}", extension.name(), error));
+            }
         }
-
-        ok_extension_names.push(extension.name());
-        selection = search_result;
     }
+    let selection = selection.ok_or(format_err!(
+        "Extensions have failed to find package in package registries.\n{}",
+        error_messages.join("\n")
+    ));
 
     if ok_extension_names.len() > 1 {
         Err(format_err!(
@@ -89,3 +96,213 @@ pub fn identify_local_dependencies(
     })
     .unwrap()
 }
+
+/// Identify dependencies from a given dependencies specification file.
+///
+/// The file name is given separately because the file may be a copy, for example of stdin.
+/// If the file name is unknown, the file format is identified from its content.
+///
+/// Conducts a parallel search across extensions. Raises an error unless exactly one extension
+/// parses the file.
+pub fn identify_file_dependencies<'a>(
+    extensions: &'a Vec<Box<dyn vouch_lib::extension::Extension>>,
+    file_path: &std::path::PathBuf,
+    file_name: Option<&str>,
+) -> Result<(
+    &'a Box<dyn vouch_lib::extension::Extension>,
+    vouch_lib::extension::DependenciesSpec,
+)> {
+    let results = crossbeam_utils::thread::scope(|s| {
+        let mut threads = Vec::new();
+        for extension in extensions {
+            threads.push(s.spawn(move |_| {
+                identify_extension_file_dependencies(extension, &file_path, file_name)
+            }));
+        }
+        let mut result = Vec::new();
+        for thread in threads {
+            result.push(thread.join().unwrap());
+        }
+        result
+    })
+    .unwrap();
+
+    let mut selection = Vec::new();
+    let mut error_messages = Vec::new();
+    for (result, extension) in results.into_iter().zip(extensions.iter()) {
+        match result {
+            Ok(dependencies_spec) => selection.push((extension, dependencies_spec)),
+            Err(error) => {
+                log::debug!("Extension {} error: {}", extension.name(), error);
+                error_messages.push(format!("{}: {}", extension.name(), error));
+            }
+        }
+    }
+
+    if selection.len() > 1 {
+        return Err(format_err!(
+            "Multiple extensions can parse dependencies file: {path}\n\
+            Please specify an extension using --format.\n\
+            Matching extensions: {names}",
+            path = file_path.display(),
+            names = selection
+                .iter()
+                .map(|(extension, _)| extension.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    selection.pop().ok_or(format_err!(
+        "Extensions failed to parse dependencies file: {path}\n{errors}",
+        path = file_path.display(),
+        errors = error_messages.join("\n")
+    ))
+}
+
+/// Identify dependencies from a given file using a single extension.
+///
+/// Extensions which can not parse a given file are given a temporary working directory which
+/// holds a copy of the file. The copy is named after the original file or, if the file name is
+/// unknown, after each of the extension's common dependencies file names in turn.
+fn identify_extension_file_dependencies(
+    extension: &Box<dyn vouch_lib::extension::Extension>,
+    file_path: &std::path::PathBuf,
+    file_name: Option<&str>,
+) -> Result<vouch_lib::extension::DependenciesSpec> {
+    if extension
+        .capabilities()
+        .contains(&vouch_lib::extension::Capability::IdentifyFileDependencies)
+    {
+        return extension.identify_file_dependencies(&file_path);
+    }
+
+    let file_names = match file_name {
+        Some(file_name) => vec![file_name],
+        None => get_dependencies_file_names(&extension.name()),
+    };
+    if file_names.is_empty() {
+        return Err(format_err!(
+            "Extension {name} can not identify the dependencies file format. \
+            Please use --file with the original file name.",
+            name = extension.name()
+        ));
+    }
+
+    let mut error_messages = Vec::new();
+    for file_name in file_names {
+        match identify_file_dependencies_in_directory(extension, &file_path, file_name) {
+            Ok(Some(mut dependencies_spec)) => {
+                dependencies_spec.path = file_path.clone();
+                return Ok(dependencies_spec);
+            }
+            Ok(None) => {}
+            Err(error) => error_messages.push(format!("{}: {}", file_name, error)),
+        }
+    }
+    Err(format_err!(
+        "Extension {name} does not support dependencies file: {path}\n{errors}",
+        name = extension.name(),
+        path = file_path.display(),
+        errors = error_messages.join("\n")
+    ))
+}
+
+/// Copies the file into a temporary directory under the given name and returns the dependencies
+/// which the extension identifies for the copy.
+fn identify_file_dependencies_in_directory(
+    extension: &Box<dyn vouch_lib::extension::Extension>,
+    file_path: &std::path::PathBuf,
+    file_name: &str,
+) -> Result<Option<vouch_lib::extension::DependenciesSpec>> {
+    let tmp_dir = tempdir::TempDir::new("vouch_check_file")?;
+    let directory = tmp_dir.path().canonicalize()?;
+    let copy_path = directory.join(file_name);
+    std::fs::copy(&file_path, &copy_path)?;
+
+    let dependencies_specs = extension.identify_local_dependencies(&directory)?;
+    Ok(dependencies_specs
+        .into_iter()
+        .find(|dependencies_spec| dependencies_spec.path == copy_path))
+}
+
+/// Returns common dependencies file names for built-in extensions.
+fn get_dependencies_file_names(extension_name: &str) -> Vec<&'static str> {
+    match extension_name {
+        "py" => vec!["Pipfile.lock", "poetry.lock"],
+        "js" => vec!["package-lock.json", "yarn.lock"],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Extension which only discovers `Pipfile.lock` files in the working directory.
+    struct DirectoryExtension;
+
+    impl vouch_lib::extension::Extension for DirectoryExtension {
+        fn name(&self) -> String {
+            "py".to_string()
+        }
+
+        fn registries(&self) -> Vec<String> {
+            vec!["pypi.org".to_string()]
+        }
+
+        fn identify_local_dependencies(
+            &self,
+            working_directory: &std::path::PathBuf,
+        ) -> Result<Vec<vouch_lib::extension::DependenciesSpec>> {
+            let path = working_directory.join("Pipfile.lock");
+            if !path.is_file() {
+                return Ok(Vec::new());
+            }
+            Ok(vec![vouch_lib::extension::DependenciesSpec {
+                path,
+                registry_host_name: "pypi.org".to_string(),
+                dependencies: Vec::new(),
+            }])
+        }
+
+        fn registries_package_metadata(
+            &self,
+            _package_name: &str,
+            _package_version: &str,
+        ) -> Result<Vec<vouch_lib::extension::RegistryPackageMetadata>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn get_extensions() -> Vec<Box<dyn vouch_lib::extension::Extension>> {
+        vec![Box::new(DirectoryExtension) as Box<dyn vouch_lib::extension::Extension>]
+    }
+
+    #[test]
+    fn test_identify_file_dependencies_falls_back_to_directory_search() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("vouch_test_file_dependencies")?;
+        let file_path = tmp_dir.path().join("subproject-Pipfile.lock");
+        std::fs::write(&file_path, "{}")?;
+        let extensions = get_extensions();
+
+        let result = identify_file_dependencies(&extensions, &file_path, Some("Pipfile.lock"));
+        let (_extension, dependencies_spec) = result?;
+        assert_eq!(dependencies_spec.path, file_path);
+
+        let result = identify_file_dependencies(&extensions, &file_path, Some("poetry.lock"));
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_identify_file_dependencies_tries_ecosystem_file_names() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("vouch_test_stdin_dependencies")?;
+        let file_path = tmp_dir.path().join("stdin");
+        std::fs::write(&file_path, "{}")?;
+
+        let (_extension, dependencies_spec) =
+            identify_file_dependencies(&get_extensions(), &file_path, None)?;
+        assert_eq!(dependencies_spec.path, file_path);
+        Ok(())
+    }
+}
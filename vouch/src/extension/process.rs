@@ -7,23 +7,53 @@ use crate::extension::common;
 pub static EXTENSION_FILE_NAME_PREFIX: &str = "vouch-";
 
 /// Return handles to all known extensions.
-pub fn get_all() -> Result<Vec<Box<dyn vouch_lib::extension::Extension>>> {
+pub fn get_all(
+    config: &crate::common::config::Config,
+) -> Result<Vec<Box<dyn vouch_lib::extension::Extension>>> {
+    Ok(get_all_with_paths(&config)?
+        .into_iter()
+        .map(|(extension, _process_path)| extension)
+        .collect())
+}
+
+/// Extension handle with process binary path. Built-in extensions do not have a path.
+pub type ExtensionWithPath = (
+    Box<dyn vouch_lib::extension::Extension>,
+    Option<std::path::PathBuf>,
+);
+
+/// Return handles to all known extensions with process binary paths.
+pub fn get_all_with_paths(
+    config: &crate::common::config::Config,
+) -> Result<Vec<ExtensionWithPath>> {
     log::debug!("Identifying all extensions.");
 
     let mut all_extensions = vec![
-        Box::new(vouch_py_lib::PyExtension::new()) as Box<dyn vouch_lib::extension::Extension>,
-        Box::new(vouch_js_lib::JsExtension::new()) as Box<dyn vouch_lib::extension::Extension>,
+        (
+            Box::new(vouch_py_lib::PyExtension::new()) as Box<dyn vouch_lib::extension::Extension>,
+            None,
+        ),
+        (
+            Box::new(vouch_js_lib::JsExtension::new()) as Box<dyn vouch_lib::extension::Extension>,
+            None,
+        ),
     ];
 
-    for extension in get_process_extensions()? {
-        all_extensions.push(Box::new(extension) as Box<dyn vouch_lib::extension::Extension>);
+    for extension in get_process_extensions(config.extensions.get_timeout())? {
+        let process_path = extension.process_path().clone();
+        all_extensions.push((
+            Box::new(extension) as Box<dyn vouch_lib::extension::Extension>,
+            Some(process_path),
+        ));
     }
 
     Ok(all_extensions)
 }
 
 /// Discovers and loads process extensions.
-fn get_process_extensions() -> Result<Vec<vouch_lib::extension::process::ProcessExtension>> {
+fn get_process_extensions(
+    timeout: Option<std::time::Duration>,
+) -> Result<Vec<vouch_lib::extension::process::ProcessExtension>> {
     let extension_paths = get_extension_paths()?;
 
     let mut threads = vec![];
@@ -35,6 +65,7 @@ fn get_process_extensions() -> Result<Vec<vouch_lib::extension::process::Process
             vouch_lib::extension::process::ProcessExtension::from_process(
                 &process_path,
                 &extension_config_path,
+                timeout,
             )
         }));
     }
@@ -55,3 +55,13 @@ impl crate::common::HashSansId for Package {
         self.artifact_hash.hash(state);
     }
 }
+
+impl common::ContentHash for Package {
+    fn content_hash(&self) -> String {
+        common::get_content_hash(&serde_json::json!({
+            "name": self.name,
+            "version": self.version,
+            "artifact_hash": self.artifact_hash,
+        }))
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use std::collections::HashSet;
 
 use super::common;
-use crate::common::StoreTransaction;
+use crate::common::{ContentHash, StoreTransaction};
 use crate::registry;
 
 #[derive(Debug, Default)]
@@ -10,6 +10,7 @@ pub struct Fields<'a> {
     pub id: Option<crate::common::index::ID>,
     pub package_name: Option<&'a str>,
     pub package_version: Option<&'a str>,
+    pub content_hash: Option<&'a str>,
 
     // Filters match for any in set.
     pub registry_host_names: Option<std::collections::BTreeSet<&'a str>>,
@@ -22,13 +23,35 @@ pub fn setup(tx: &StoreTransaction) -> Result<()> {
             id                         INTEGER NOT NULL PRIMARY KEY,
             name                       TEXT NOT NULL,
             version                    TEXT NOT NULL,
-            registry_ids               BLOB NOT NULL,
             artifact_hash              TEXT NOT NULL,
+            content_hash               TEXT,
 
             UNIQUE(name, version, artifact_hash)
         )",
         rusqlite::NO_PARAMS,
     )?;
+    tx.index_tx().execute(
+        r"
+        CREATE TABLE IF NOT EXISTS package_registry (
+            package_id                 INTEGER NOT NULL,
+            registry_id                INTEGER NOT NULL,
+
+            PRIMARY KEY(package_id, registry_id)
+            FOREIGN KEY(package_id) REFERENCES package(id) ON DELETE CASCADE
+            FOREIGN KEY(registry_id) REFERENCES registry(id)
+        )",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        r"
+        CREATE INDEX IF NOT EXISTS package_registry_registry_id_index
+        ON package_registry(registry_id)",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        "CREATE INDEX IF NOT EXISTS package_content_hash_index ON package(content_hash)",
+        rusqlite::NO_PARAMS,
+    )?;
     Ok(())
 }
 
@@ -43,107 +66,207 @@ pub fn insert(
         !registries.is_empty(),
         "At least one registry must be assigned to a package before index insert."
     );
-    let registry_ids: Vec<crate::common::index::ID> =
-        registries.into_iter().map(|c| c.id).collect();
-    let registry_ids = bincode::serialize(&registry_ids)?;
+
+    let mut package = common::Package {
+        id: 0,
+        name: package_name.to_string(),
+        version: package_version.to_string(),
+        registries: registries.clone(),
+        artifact_hash: artifact_hash.to_string(),
+    };
 
     tx.index_tx().execute_named(
         r"
             INSERT INTO package (
                 name,
                 version,
-                registry_ids,
-                artifact_hash
+                artifact_hash,
+                content_hash
             )
             VALUES (
                 :name,
                 :version,
-                :registry_ids,
-                :artifact_hash
+                :artifact_hash,
+                :content_hash
             )
         ",
         rusqlite::named_params! {
             ":name": package_name,
             ":version": package_version,
-            ":registry_ids": registry_ids,
             ":artifact_hash": artifact_hash,
+            ":content_hash": package.content_hash(),
         },
     )?;
+    let package_id = tx.index_tx().last_insert_rowid();
+    package.id = package_id;
+
+    for registry in registries {
+        tx.index_tx().execute_named(
+            r"
+                INSERT INTO package_registry (
+                    package_id,
+                    registry_id
+                )
+                VALUES (
+                    :package_id,
+                    :registry_id
+                )
+            ",
+            rusqlite::named_params! {
+                ":package_id": package_id,
+                ":registry_id": registry.id,
+            },
+        )?;
+    }
+
+    Ok(package)
+}
+
+/// Package table columns selected by `get_from_row`. Includes a single joined registry.
+pub static SELECT_COLUMNS: &str = r"
+            package.id,
+            package.name,
+            package.version,
+            package.artifact_hash,
+            registry.id,
+            registry.host_name,
+            registry.human_url,
+            registry.artifact_url";
+
+/// Join clause which provides the registry columns within `SELECT_COLUMNS`.
+pub static JOIN_REGISTRY: &str = r"
+        JOIN package_registry
+            ON package_registry.package_id = package.id
+        JOIN registry
+            ON registry.id = package_registry.registry_id";
+
+/// Parse package from row columns selected using `SELECT_COLUMNS`, starting at the given column.
+///
+/// The returned package contains the single registry found within the row.
+pub fn get_from_row(row: &rusqlite::Row<'_>, start_column: usize) -> Result<common::Package> {
     Ok(common::Package {
-        id: tx.index_tx().last_insert_rowid(),
-        name: package_name.to_string(),
-        version: package_version.to_string(),
-        registries: registries.clone(),
-        artifact_hash: artifact_hash.to_string(),
+        id: row.get(start_column)?,
+        name: row.get(start_column + 1)?,
+        version: row.get(start_column + 2)?,
+        registries: maplit::btreeset! {registry::index::get_from_row(&row, start_column + 4)?},
+        artifact_hash: row.get(start_column + 3)?,
     })
 }
 
+/// Returns an SQL clause which matches packages with any registry within the given host names.
+pub fn get_registry_host_names_clause(
+    package_id_column: &str,
+    registry_host_names: &Option<std::collections::BTreeSet<&str>>,
+) -> (String, Vec<(String, String)>) {
+    if registry_host_names.is_none() {
+        return ("true".to_string(), Vec::new());
+    }
+    let (host_names_clause, parameters) = crate::common::index::get_in_clause(
+        "registry.host_name",
+        "registry_host_name",
+        &registry_host_names,
+    );
+    let clause = format!(
+        r"
+            {package_id_column} IN (
+                SELECT package_registry.package_id
+                FROM package_registry
+                JOIN registry
+                    ON registry.id = package_registry.registry_id
+                WHERE {host_names_clause}
+            )",
+        package_id_column = package_id_column,
+        host_names_clause = host_names_clause
+    );
+    (clause, parameters)
+}
+
 pub fn get(fields: &Fields, tx: &StoreTransaction) -> Result<HashSet<common::Package>> {
     let id =
         crate::common::index::get_like_clause_param(fields.id.map(|id| id.to_string()).as_deref());
     let package_name = crate::common::index::get_like_clause_param(fields.package_name);
     let package_version = crate::common::index::get_like_clause_param(fields.package_version);
+    let (registry_host_names_clause, registry_host_names_parameters) =
+        get_registry_host_names_clause("package.id", &fields.registry_host_names);
 
-    let mut statement = tx.index_tx().prepare(
+    let sql_query = format!(
         r"
-            SELECT *
+            SELECT {select_columns}
             FROM package
+            {join_registry}
             WHERE
                 package.id LIKE :package_id ESCAPE '\'
-                AND name LIKE :name ESCAPE '\'
-                AND version LIKE :version ESCAPE '\'
+                AND package.name LIKE :name ESCAPE '\'
+                AND package.version LIKE :version ESCAPE '\'
+                AND (:content_hash IS NULL OR package.content_hash = :content_hash)
+                AND {registry_host_names_clause}
         ",
-    )?;
-    let mut rows = statement.query_named(&[
+        select_columns = SELECT_COLUMNS,
+        join_registry = JOIN_REGISTRY,
+        registry_host_names_clause = registry_host_names_clause
+    );
+    let mut statement = tx.index_tx().prepare(&sql_query)?;
+
+    let mut parameters: Vec<(&str, &dyn rusqlite::ToSql)> = vec![
         (":package_id", &id),
         (":name", &package_name),
         (":version", &package_version),
-    ])?;
+        (":content_hash", &fields.content_hash),
+    ];
+    for (name, value) in &registry_host_names_parameters {
+        parameters.push((name.as_str(), value));
+    }
+    let mut rows = statement.query_named(&parameters)?;
 
-    let mut packages = HashSet::new();
+    // Rows are produced per package registry. Gather registries by package.
+    let mut packages =
+        std::collections::BTreeMap::<crate::common::index::ID, common::Package>::new();
     while let Some(row) = rows.next()? {
-        let registry_ids: Option<Result<Vec<crate::common::index::ID>>> = row
-            .get::<_, Option<Vec<u8>>>(3)?
-            .map(|x| Ok(bincode::deserialize(&x)?));
-        let registries = match registry_ids {
-            Some(registry_ids) => {
-                let registry_ids = registry_ids?;
-                registry::index::get(
-                    &registry::index::Fields {
-                        ids: Some(&registry_ids),
-                        ..Default::default()
-                    },
-                    &tx,
-                )?
-                .into_iter()
-                .collect()
-            }
-            None => std::collections::BTreeSet::<registry::Registry>::new(),
-        };
-
-        // Skip package if none of the given registry host names match to any registry.
-        if let Some(registry_host_names) = &fields.registry_host_names {
-            let mut found_match = false;
-            for registry_host_name in registry_host_names {
-                found_match |= registries
-                    .iter()
-                    .any(|registry| &registry.host_name.as_str() == registry_host_name);
-            }
-            if !found_match {
-                continue;
+        let package = get_from_row(&row, 0)?;
+        match packages.get_mut(&package.id) {
+            Some(existing_package) => existing_package.registries.extend(package.registries),
+            None => {
+                packages.insert(package.id, package);
             }
         }
-
-        let package = common::Package {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            version: row.get(2)?,
-            registries: registries,
-            artifact_hash: row.get(4)?,
-        };
-        packages.insert(package);
     }
-    Ok(packages)
+    Ok(packages.into_iter().map(|(_id, package)| package).collect())
+}
+
+/// Returns matching package from index. Inserts package if absent.
+pub fn ensure(
+    package_name: &str,
+    package_version: &str,
+    registries: &std::collections::BTreeSet<registry::Registry>,
+    artifact_hash: &str,
+    tx: &StoreTransaction,
+) -> Result<common::Package> {
+    let registry_host_names = registries
+        .iter()
+        .map(|registry| registry.host_name.as_str())
+        .collect();
+    let package = get(
+        &Fields {
+            package_name: Some(package_name),
+            package_version: Some(package_version),
+            registry_host_names: Some(registry_host_names),
+            ..Default::default()
+        },
+        &tx,
+    )?
+    .into_iter()
+    .find(|package| package.artifact_hash == artifact_hash);
+
+    Ok(match package {
+        Some(package) => package,
+        None => insert(
+            &package_name,
+            &package_version,
+            &registries,
+            &artifact_hash,
+            &tx,
+        )?,
+    })
 }
 
 /// Merge packages from incoming index into another index. Returns the newly merged packages.
@@ -154,9 +277,15 @@ pub fn merge(
     let incoming_packages = get(&Fields::default(), &incoming_tx)?;
     let existing_packages = get(&Fields::default(), &tx)?;
 
+    let existing_hashes = existing_packages
+        .iter()
+        .map(|package| package.content_hash())
+        .collect::<HashSet<_>>();
+
     let mut new_packages = HashSet::new();
-    for package in
-        crate::common::index::get_difference_sans_id(&incoming_packages, &existing_packages)?
+    for package in incoming_packages
+        .into_iter()
+        .filter(|package| !existing_hashes.contains(&package.content_hash()))
     {
         let mut new_registries = std::collections::BTreeSet::new();
         for registry in package.registries {
@@ -184,6 +313,15 @@ pub fn merge(
 pub fn remove(fields: &Fields, tx: &StoreTransaction) -> Result<()> {
     let id =
         crate::common::index::get_like_clause_param(fields.id.map(|id| id.to_string()).as_deref());
+    tx.index_tx().execute_named(
+        r"
+        DELETE
+        FROM package_registry
+        WHERE
+            package_id LIKE :id ESCAPE '\'
+    ",
+        &[(":id", &id)],
+    )?;
     tx.index_tx().execute_named(
         r"
         DELETE
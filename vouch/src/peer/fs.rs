@@ -1,12 +1,23 @@
 use anyhow::{format_err, Result};
+use std::convert::TryFrom;
 
 use super::common;
 use crate::common::{fs::DataPaths, StoreTransaction};
 
 /// For a given Git repository URL, returns the target submodule path.
+///
+/// Local repositories are stored under a `file` directory, e.g. `file/home/user/reviews.git`.
 fn get_submodule_storage_relative_path(
     git_url: &crate::common::GitUrl,
 ) -> Result<std::path::PathBuf> {
+    if git_url.is_local() {
+        // Drive letter colons are not valid within Windows path components.
+        let url_path_component = git_url.url().path().replace(':', "");
+        let url_path_component = std::path::PathBuf::from(url_path_component);
+        let url_path_component = url_path_component.strip_prefix("/")?;
+        return Ok(std::path::PathBuf::from("file").join(url_path_component));
+    }
+
     let url_registry_component = git_url.url().host_str().ok_or(format_err!(
         "Cannot derive registry from URL: {:?}",
         git_url
@@ -26,7 +37,12 @@ pub fn add(git_url: &crate::common::GitUrl, _tx: &mut StoreTransaction) -> Resul
     let peers_directory_name = paths.peers_directory.strip_prefix(&paths.root_directory)?;
     let submodule_relative_path = peers_directory_name.join(submodule_relative_path);
 
-    let args = vec![
+    let mut args = Vec::new();
+    if git_url.is_local() {
+        // Git refuses to clone local submodules unless file transport is explicitly allowed.
+        args.extend(vec!["-c", "protocol.file.allow=always"]);
+    }
+    args.extend(vec![
         "submodule",
         "add",
         "--depth",
@@ -36,7 +52,7 @@ pub fn add(git_url: &crate::common::GitUrl, _tx: &mut StoreTransaction) -> Resul
             "Could not parse submodule path: {:?}",
             submodule_relative_path
         ))?,
-    ];
+    ]);
     crate::common::fs::git(args, &paths.root_directory)?;
     Ok(())
 }
@@ -46,11 +62,11 @@ pub fn get_root_database() -> Result<rusqlite::Connection> {
     Ok(rusqlite::Connection::open(paths.index_file)?)
 }
 
-pub fn get_peer_database(peer_branch: &Vec<common::Peer>) -> Result<rusqlite::Connection> {
+pub fn get_peer_database_path(peer_branch: &Vec<common::Peer>) -> Result<std::path::PathBuf> {
     let root_peer_paths = DataPaths::new()?;
     let peer_path = get_peer_path(&peer_branch, &root_peer_paths.root_directory)?;
     let paths = DataPaths::from_root_directory(&peer_path)?;
-    Ok(rusqlite::Connection::open(paths.index_file)?)
+    Ok(paths.index_file)
 }
 
 pub fn remove(peer_branch: &Vec<common::Peer>, tx: &mut StoreTransaction) -> Result<()> {
@@ -77,19 +93,51 @@ pub fn remove(peer_branch: &Vec<common::Peer>, tx: &mut StoreTransaction) -> Res
 
 /// Given a top level (directly followed) peer, fetches from origin/master.
 /// Returns true if a remote update is available.
-///
-/// See: https://stackoverflow.com/questions/58768910/how-to-perform-git-pull-with-the-rust-git2-crate
 pub fn fetch_update(peer: &common::Peer, _tx: &mut StoreTransaction) -> Result<bool> {
     log::debug!("Fetching updates for top level peer: {}", peer.git_url);
     let paths = DataPaths::new()?;
 
+    let submodule_relative_path = get_submodule_storage_relative_path(&peer.git_url)?;
+    let peer_submodule_path = paths.peers_directory.join(&submodule_relative_path);
+    fetch_repository_update(&peer_submodule_path)
+}
+
+pub fn merge_update(peer: &common::Peer, _tx: &mut StoreTransaction) -> Result<()> {
+    let paths = DataPaths::new()?;
+
     let submodule_relative_path = get_submodule_storage_relative_path(&peer.git_url)?;
     let peer_submodule_path = paths.peers_directory.join(&submodule_relative_path);
 
-    let repo = git2::Repository::open(&peer_submodule_path)?;
+    if !merge_repository_update(&peer_submodule_path)? {
+        return Err(format_err!(
+            "Peer update cannot be merged using fast forward: {:?}",
+            peer
+        ));
+    }
+    Ok(())
+}
+
+/// Fetches from origin. Returns true if a remote update is available.
+///
+/// See: https://stackoverflow.com/questions/58768910/how-to-perform-git-pull-with-the-rust-git2-crate
+fn fetch_repository_update(repository_path: &std::path::PathBuf) -> Result<bool> {
+    let repo = git2::Repository::open(&repository_path)?;
     // TODO: Add git2 credentials handling.
     // repo.find_remote("origin")?.fetch(&["master"], None, None)?;
-    crate::common::fs::git(vec!["fetch"], &peer_submodule_path)?;
+    let status = crate::common::fs::git(vec!["fetch"], &repository_path)?;
+    if !status.success() {
+        return Err(format_err!(
+            "Failed to fetch updates for repository: {}",
+            repository_path.display()
+        ));
+    }
+
+    if repo.is_shallow() {
+        // git2 merge analysis can not walk shallow clones. Compare commits instead.
+        let head_commit = repo.head()?.peel_to_commit()?;
+        let fetch_commit = repo.find_reference("FETCH_HEAD")?.peel_to_commit()?;
+        return Ok(head_commit.id() != fetch_commit.id());
+    }
 
     let fetch_head = repo.find_reference("FETCH_HEAD")?;
     let fetch_commit = repo.reference_to_annotated_commit(&fetch_head)?;
@@ -97,37 +145,68 @@ pub fn fetch_update(peer: &common::Peer, _tx: &mut StoreTransaction) -> Result<b
     Ok(!analysis.0.is_up_to_date())
 }
 
-pub fn merge_update(peer: &common::Peer, _tx: &mut StoreTransaction) -> Result<()> {
-    let paths = DataPaths::new()?;
-
-    let submodule_relative_path = get_submodule_storage_relative_path(&peer.git_url)?;
-    let peer_submodule_path = paths.peers_directory.join(&submodule_relative_path);
-
-    let repo = git2::Repository::open(&peer_submodule_path)?;
+/// Fast forwards the checked out commit to the last fetched commit. Returns false if the update
+/// can not be merged using fast forward.
+fn merge_repository_update(repository_path: &std::path::PathBuf) -> Result<bool> {
+    let repo = git2::Repository::open(&repository_path)?;
 
     // Do not re-fetch incase the fetch has changed since first discovering a new available update.
+    if repo.is_shallow() {
+        // Git fast-forwards shallow clones correctly, unlike git2 merge analysis.
+        let status = crate::common::fs::git(
+            vec!["merge", "--ff-only", "--quiet", "FETCH_HEAD"],
+            &repository_path,
+        )?;
+        return Ok(status.success());
+    }
+
     let fetch_head = repo.find_reference("FETCH_HEAD")?;
     let fetch_commit = repo.reference_to_annotated_commit(&fetch_head)?;
     let analysis = repo.merge_analysis(&[&fetch_commit])?;
+    if !analysis.0.is_fast_forward() {
+        return Ok(false);
+    }
 
-    assert!(
-        !analysis.0.is_up_to_date(),
-        "This function should only be called if fetch has indicated that an update is available."
-    );
-
-    if analysis.0.is_fast_forward() {
-        let reference_name = "refs/heads/master";
-        let mut reference = repo.find_reference(&reference_name)?;
-        reference.set_target(fetch_commit.id(), "Fast-Forward")?;
-        repo.set_head(&reference_name)?;
-        repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+    // Local repositories are often created with a default branch other than master.
+    let head = repo.head()?;
+    let reference_name = if head.is_branch() {
+        head.name().unwrap_or("refs/heads/master").to_string()
     } else {
-        return Err(format_err!(
-            "Peer update cannot be merged using fast forward: {:?}",
-            peer
-        ));
+        "refs/heads/master".to_string()
+    };
+    let mut reference = repo.find_reference(&reference_name)?;
+    reference.set_target(fetch_commit.id(), "Fast-Forward")?;
+    repo.set_head(&reference_name)?;
+    repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
+    Ok(true)
+}
+
+/// Returns the time at which the peer's repository was last fetched.
+///
+/// Falls back to the time of the checked out commit for peers which have never been fetched
+/// since they were added. Returns None if the peer repository is not available locally.
+pub fn get_last_sync_time(
+    peer_branch: &Vec<common::Peer>,
+) -> Result<Option<std::time::SystemTime>> {
+    let paths = DataPaths::new()?;
+    let peer_path = get_peer_path(&peer_branch, &paths.root_directory)?;
+    let repo = match git2::Repository::open(&peer_path) {
+        Ok(repo) => repo,
+        Err(_) => return Ok(None),
+    };
+
+    if let Ok(metadata) = std::fs::metadata(repo.path().join("FETCH_HEAD")) {
+        return Ok(Some(metadata.modified()?));
     }
-    Ok(())
+
+    let commit = match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(commit) => commit,
+        Err(_) => return Ok(None),
+    };
+    let seconds = std::cmp::max(commit.time().seconds(), 0) as u64;
+    Ok(Some(
+        std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds),
+    ))
 }
 
 fn remove_direct_follow(peer: &common::Peer, _tx: &mut StoreTransaction) -> Result<()> {
@@ -152,6 +231,29 @@ fn remove_indirect_follow(peer_branch: &Vec<common::Peer>) -> Result<()> {
     Ok(())
 }
 
+/// Returns the git URLs and absolute paths of peers which are submodules of a given repository.
+pub fn get_child_peers(
+    repository_directory: &std::path::PathBuf,
+) -> Result<Vec<(crate::common::GitUrl, std::path::PathBuf)>> {
+    let paths = DataPaths::from_root_directory(&repository_directory)?;
+    let peers_directory_name = paths.peers_directory.strip_prefix(&paths.root_directory)?;
+
+    let repo = git2::Repository::open(&repository_directory)?;
+    let mut child_peers = Vec::new();
+    for submodule in repo.submodules()? {
+        if !submodule.path().starts_with(&peers_directory_name) {
+            continue;
+        }
+        let url = submodule.url().ok_or(format_err!(
+            "Failed to parse URL for submodule: {}",
+            submodule.path().display()
+        ))?;
+        let git_url = crate::common::GitUrl::try_from(url)?;
+        child_peers.push((git_url, repository_directory.join(submodule.path())));
+    }
+    Ok(child_peers)
+}
+
 pub fn get_peer_path(
     peer_branch: &Vec<common::Peer>,
     root_directory: &std::path::PathBuf,
@@ -173,7 +275,6 @@ pub fn get_peer_path(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::convert::TryFrom;
 
     #[test]
     fn test_three_peer_branch_derived_correct_path() -> Result<()> {
@@ -202,4 +303,78 @@ mod tests {
         assert_eq!(result, expected);
         Ok(())
     }
+
+    fn git_commit(message: &str, repository_path: &std::path::PathBuf) -> Result<git2::Oid> {
+        let status = crate::common::fs::git(
+            vec![
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@localhost",
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "-m",
+                message,
+            ],
+            &repository_path,
+        )?;
+        assert!(status.success());
+        let repo = git2::Repository::open(&repository_path)?;
+        let commit_id = repo.head()?.peel_to_commit()?.id();
+        Ok(commit_id)
+    }
+
+    /// Clones a local repository with the given extra clone arguments, then fetches and merges a
+    /// new remote commit.
+    fn assert_local_peer_update_fast_forwarded(clone_args: Vec<&str>) -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("vouch_test_local_peer_update")?;
+        let remote_path = tmp_dir.path().join("reviews.git");
+        let peer_path = tmp_dir.path().join("peer");
+        git2::Repository::init(&remote_path)?;
+        git_commit("Initial commit.", &remote_path)?;
+
+        let remote_url = url::Url::from_file_path(&remote_path).unwrap();
+        let mut args = vec!["clone", "--quiet"];
+        args.extend(clone_args);
+        args.extend(vec![remote_url.as_str(), peer_path.to_str().unwrap()]);
+        let status = crate::common::fs::git(args, &tmp_dir.path().to_path_buf())?;
+        assert!(status.success());
+        assert!(!fetch_repository_update(&peer_path)?);
+
+        let expected = git_commit("Add review.", &remote_path)?;
+        assert!(fetch_repository_update(&peer_path)?);
+        assert!(merge_repository_update(&peer_path)?);
+
+        let result = git2::Repository::open(&peer_path)?
+            .head()?
+            .peel_to_commit()?
+            .id();
+        assert_eq!(result, expected);
+        assert!(!fetch_repository_update(&peer_path)?);
+
+        // A failed fetch must not report an update from a stale FETCH_HEAD.
+        std::fs::remove_dir_all(&remote_path)?;
+        assert!(fetch_repository_update(&peer_path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_shallow_local_peer_update_fast_forwarded() -> Result<()> {
+        assert_local_peer_update_fast_forwarded(vec!["--depth", "1"])
+    }
+
+    #[test]
+    fn test_full_local_peer_update_fast_forwarded() -> Result<()> {
+        assert_local_peer_update_fast_forwarded(vec![])
+    }
+
+    #[test]
+    fn test_local_peer_storage_path() -> Result<()> {
+        let git_url = crate::common::GitUrl::try_from("file:///home/user/reviews.git")?;
+        let result = get_submodule_storage_relative_path(&git_url)?;
+        let expected = std::path::PathBuf::from("file/home/user/reviews.git");
+        assert_eq!(result, expected);
+        Ok(())
+    }
 }
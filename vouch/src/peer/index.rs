@@ -1,5 +1,4 @@
 use anyhow::{format_err, Result};
-use bincode;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::convert::TryFrom;
 
@@ -12,7 +11,6 @@ pub struct Fields<'a> {
     pub alias: Option<&'a str>,
     pub git_url: Option<&'a crate::common::GitUrl>,
     pub parent_id: Option<crate::common::index::ID>,
-    pub child_peer_ids: Option<common::SubPeerIds>,
 }
 
 /// Returns the root peer.
@@ -37,12 +35,15 @@ pub fn setup(tx: &StoreTransaction) -> Result<()> {
         alias           TEXT NOT NULL UNIQUE,
         git_url         TEXT NOT NULL UNIQUE,
         parent_id       INTEGER,
-        child_peer_ids  BLOB,
 
         FOREIGN KEY(parent_id) REFERENCES peer(id)
     )",
         rusqlite::NO_PARAMS,
     )?;
+    tx.index_tx().execute(
+        "CREATE INDEX IF NOT EXISTS peer_parent_id_index ON peer(parent_id)",
+        rusqlite::NO_PARAMS,
+    )?;
 
     // Insert root peer if absent.
     let found_root_peer = !get(
@@ -78,15 +79,10 @@ pub fn insert(
     };
     tx.index_tx().execute(
         "
-        INSERT INTO peer (alias, git_url, parent_id, child_peer_ids)
-            VALUES (?1, ?2, ?3, ?4)
+        INSERT INTO peer (alias, git_url, parent_id)
+            VALUES (?1, ?2, ?3)
         ",
-        rusqlite::params![
-            alias,
-            git_url.to_string(),
-            parent_id,
-            None as Option<Vec<u8>>
-        ],
+        rusqlite::params![alias, git_url.to_string(), parent_id],
     )?;
     let new_peer = common::Peer {
         id: tx.index_tx().last_insert_rowid(),
@@ -96,67 +92,56 @@ pub fn insert(
         child_peer_ids: None,
     };
 
+    // Keep given parent peer coherent with the index.
     if let Some(parent_peer) = parent_peer {
-        add_child_peer_id(parent_peer, &new_peer, &tx)?;
+        parent_peer
+            .child_peer_ids
+            .get_or_insert_with(|| common::SubPeerIds(BTreeSet::new()))
+            .0
+            .insert(new_peer.id);
     }
 
     Ok(new_peer)
 }
 
-/// Given a peer, extend its child peer set.
-fn add_child_peer_id(
-    peer: &mut common::Peer,
-    child_peer: &common::Peer,
-    tx: &StoreTransaction,
-) -> Result<()> {
-    if peer.child_peer_ids.is_none() {
-        peer.child_peer_ids = Some(common::SubPeerIds(BTreeSet::new()));
-    }
-
-    if let Some(child_peer_ids) = &mut peer.child_peer_ids {
-        child_peer_ids.0.insert(child_peer.id);
-        tx.index_tx().execute(
-            r"
-            UPDATE peer
-            SET child_peer_ids = ?2
-            WHERE id = ?1
-        ",
-            rusqlite::params![peer.id, bincode::serialize(&child_peer_ids)?,],
-        )?;
-    }
-    Ok(())
-}
-
-/// Given a peer, remove a peer from its child peer set.
-fn remove_child_peer_id(
-    peer: &mut common::Peer,
-    child_peer: &common::Peer,
-    tx: &StoreTransaction,
-) -> Result<()> {
-    if peer.child_peer_ids.is_none() {
-        return Ok(());
-    }
-
-    if let Some(child_peer_ids) = &mut peer.child_peer_ids {
-        let child_peer_removed = child_peer_ids.0.remove(&child_peer.id);
-        if child_peer_removed {
-            let child_peer_ids = if child_peer_ids.0.is_empty() {
-                None as Option<Vec<u8>>
-            } else {
-                Some(bincode::serialize(&child_peer_ids)?)
-            };
-
-            tx.index_tx().execute(
-                r"
-            UPDATE peer
-            SET child_peer_ids = ?2
-            WHERE id = ?1
-        ",
-                rusqlite::params![peer.id, child_peer_ids,],
-            )?;
+/// Peer table columns selected by `get_from_row`. Child peer IDs are derived from parent IDs.
+pub static SELECT_COLUMNS: &str = r"
+            peer.id,
+            peer.alias,
+            peer.git_url,
+            peer.parent_id,
+            (
+                SELECT group_concat(child_peer.id)
+                FROM peer AS child_peer
+                WHERE child_peer.parent_id = peer.id
+            )";
+
+/// Parse peer from row columns selected using `SELECT_COLUMNS`, starting at the given column.
+pub fn get_from_row(row: &rusqlite::Row<'_>, start_column: usize) -> Result<common::Peer> {
+    let git_url = crate::common::GitUrl::try_from(&row.get::<_, String>(start_column + 2)?)?;
+    let child_peer_ids = match row.get::<_, Option<String>>(start_column + 4)? {
+        Some(child_peer_ids) => {
+            let child_peer_ids = child_peer_ids
+                .split(",")
+                .map(|id| id.parse::<crate::common::index::ID>())
+                .collect::<std::result::Result<BTreeSet<_>, _>>()
+                .map_err(|_| {
+                    format_err!(
+                        "Failed to parse field `child_peer_ids` for peer: {git_url}",
+                        git_url = git_url
+                    )
+                })?;
+            Some(common::SubPeerIds(child_peer_ids))
         }
-    }
-    Ok(())
+        None => None,
+    };
+    Ok(common::Peer {
+        id: row.get(start_column)?,
+        alias: row.get(start_column + 1)?,
+        git_url,
+        parent_id: row.get(start_column + 3)?,
+        child_peer_ids,
+    })
 }
 
 /// Get matching peers.
@@ -170,16 +155,19 @@ pub fn get(fields: &Fields, tx: &StoreTransaction) -> Result<HashSet<common::Pee
         fields.parent_id.map(|id| id.to_string()).as_deref(),
     );
 
-    let sql_query = r"
-        SELECT *
+    let sql_query = format!(
+        r"
+        SELECT {select_columns}
         FROM peer
         WHERE
             id LIKE :id ESCAPE '\'
             AND alias LIKE :alias ESCAPE '\'
             AND git_url LIKE :git_url ESCAPE '\'
             AND ifnull(parent_id, '') LIKE :parent_id ESCAPE '\'
-    ";
-    let mut statement = tx.index_tx().prepare(sql_query)?;
+    ",
+        select_columns = SELECT_COLUMNS
+    );
+    let mut statement = tx.index_tx().prepare(&sql_query)?;
     let mut rows = statement.query_named(&[
         (":id", &id),
         (":alias", &alias),
@@ -188,34 +176,44 @@ pub fn get(fields: &Fields, tx: &StoreTransaction) -> Result<HashSet<common::Pee
     ])?;
     let mut peers = HashSet::new();
     while let Some(row) = rows.next()? {
-        let git_url = crate::common::GitUrl::try_from(&row.get::<_, String>(2)?)?;
-        let child_peer_ids: Option<Result<common::SubPeerIds>> = row
-            .get::<_, Option<Vec<u8>>>(4)?
-            .map(|x| Ok(bincode::deserialize(&x)?));
-        let child_peer_ids = match child_peer_ids {
-            Some(v) => Some(v.map_err(|_| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Blob,
-                    Box::from(format!(
-                        "Failed to parse field `child_peer_ids` for peer: {git_url}",
-                        git_url = git_url
-                    )),
-                )
-            })?),
-            None => None,
-        };
-        peers.insert(common::Peer {
-            id: row.get(0)?,
-            alias: row.get(1)?,
-            git_url,
-            parent_id: row.get(3)?,
-            child_peer_ids,
-        });
+        peers.insert(get_from_row(&row, 0)?);
     }
     Ok(peers)
 }
 
+/// Find a peer by alias or, failing that, by git URL.
+pub fn get_by_alias_or_git_url(
+    alias_or_git_url: &str,
+    tx: &StoreTransaction,
+) -> Result<Option<common::Peer>> {
+    let peer = get(
+        &Fields {
+            alias: Some(alias_or_git_url),
+            ..Default::default()
+        },
+        &tx,
+    )?
+    .into_iter()
+    .next();
+    if peer.is_some() {
+        return Ok(peer);
+    }
+
+    let git_url = match crate::common::GitUrl::try_from(alias_or_git_url) {
+        Ok(git_url) => git_url,
+        Err(_) => return Ok(None),
+    };
+    Ok(get(
+        &Fields {
+            git_url: Some(&git_url),
+            ..Default::default()
+        },
+        &tx,
+    )?
+    .into_iter()
+    .next())
+}
+
 /// Remove peer.
 pub fn remove(fields: &Fields, tx: &StoreTransaction) -> Result<()> {
     let peer = match get(&fields, &tx)?.into_iter().next() {
@@ -230,23 +228,12 @@ pub fn remove(fields: &Fields, tx: &StoreTransaction) -> Result<()> {
         );
     }
 
-    // Remove peer from its parent's child peer set.
-    let parent_peer_id = peer.parent_id.ok_or(format_err!(
-        "Peer does not have a parent peer. \
-    Peer must therefore be the root peer. Cannot remove root peer."
-    ))?;
-    let mut parent_peer = get(
-        &Fields {
-            id: Some(parent_peer_id),
-            ..Default::default()
-        },
-        &tx,
-    )?
-    .into_iter()
-    .next()
-    .ok_or(format_err!("Parent peer not found in index."))?
-    .clone();
-    remove_child_peer_id(&mut parent_peer, &peer, &tx)?;
+    if peer.parent_id.is_none() {
+        return Err(format_err!(
+            "Peer does not have a parent peer. \
+        Peer must therefore be the root peer. Cannot remove root peer."
+        ));
+    }
 
     let peer_id = crate::common::index::get_like_clause_param(Some(&peer.id.to_string()));
     tx.index_tx().execute_named(
@@ -293,8 +280,13 @@ pub fn get_peer_branch(peer: &common::Peer, tx: &StoreTransaction) -> Result<Vec
 }
 
 /// Merge peers from incoming index into another index. Returns the newly merged peers.
+///
+/// Sub-peers which are blocked or excluded by the given options are skipped along with their own
+/// subtrees.
 pub fn merge(
     incoming_root_git_url: &crate::common::GitUrl,
+    options: &crate::common::config::PeerOptions,
+    blocklist: &crate::common::config::Blocklist,
     incoming_tx: &StoreTransaction,
     tx: &StoreTransaction,
 ) -> Result<HashSet<common::Peer>> {
@@ -335,7 +327,7 @@ pub fn merge(
     ))?;
 
     for subtree in get_peer_subtrees(None, &incoming_tx)? {
-        for peer_pair in subtree.windows(2) {
+        for (depth, peer_pair) in (1..).zip(subtree.windows(2)) {
             let parent_peer = &peer_pair[0];
             let peer = &peer_pair[1];
 
@@ -352,6 +344,19 @@ pub fn merge(
                 insert_peer(&parent_peer, Some(&root_peer))?;
             }
 
+            if blocklist.is_blocked(&peer.git_url) {
+                log::info!("Skipping blocked peer: {}", peer.git_url);
+                break;
+            }
+            if !options.is_peer_allowed(&peer.git_url, depth) {
+                log::debug!(
+                    "Skipping excluded peer (depth {depth}): {git_url}",
+                    depth = depth,
+                    git_url = peer.git_url
+                );
+                break;
+            }
+
             insert_peer(peer, Some(&parent_peer))?;
         }
     }
@@ -372,6 +377,15 @@ pub fn get_new_alias(git_url: &crate::common::GitUrl, tx: &StoreTransaction) ->
                 alias = first_segment;
             }
         }
+    } else if git_url.is_local() {
+        // Name local repositories after their directory, e.g. `reviews` for `/tmp/reviews.git`.
+        let last_segment = git_url
+            .url()
+            .path_segments()
+            .and_then(|segments| segments.filter(|segment| !segment.is_empty()).last());
+        if let Some(last_segment) = last_segment {
+            alias = last_segment.strip_suffix(".git").unwrap_or(last_segment);
+        }
     };
 
     // Root alias is reserved for the root peer only.
@@ -425,6 +439,22 @@ pub fn get_breadth_first_child_peers(
     Ok(breadth_layers)
 }
 
+/// Returns indirectly followed peers which are not among the given approved git URLs.
+pub fn get_quarantined(
+    approved_git_urls: &BTreeSet<String>,
+    tx: &StoreTransaction,
+) -> Result<HashSet<common::Peer>> {
+    let root_peer = get_root(&tx)?.ok_or(format_err!("Cannot find root peer."))?;
+
+    // Layers: root peer, directly followed peers, indirectly followed peers.
+    Ok(get_breadth_first_child_peers(&root_peer, &tx)?
+        .into_iter()
+        .skip(2)
+        .flatten()
+        .filter(|peer| !approved_git_urls.contains(peer.git_url.as_str()))
+        .collect())
+}
+
 fn get_peer_subtrees(
     starting_subtree: Option<&Vec<common::Peer>>,
     tx: &StoreTransaction,
@@ -525,7 +555,13 @@ mod tests {
         // root -> peer_3 -> peer_2
         let incoming_root_git_url =
             crate::common::GitUrl::try_from("https://localhost/root_incoming")?;
-        merge(&incoming_root_git_url, &incoming_tx, &tx)?;
+        merge(
+            &incoming_root_git_url,
+            &Default::default(),
+            &Default::default(),
+            &incoming_tx,
+            &tx,
+        )?;
 
         let result: HashSet<common::Peer> = get(&Fields::default(), &tx)?.into_iter().collect();
         let expected = maplit::hashset! {
@@ -616,7 +652,13 @@ mod tests {
         // Merge incoming and destination databases.
         // root -> root_incoming -> peer_1
         // root -> peer_3 -> peer_2
-        let result = merge(&incoming_root_git_url, &incoming_tx, &tx)?;
+        let result = merge(
+            &incoming_root_git_url,
+            &Default::default(),
+            &Default::default(),
+            &incoming_tx,
+            &tx,
+        )?;
 
         let expected = maplit::hashset! {
             common::Peer {
@@ -745,4 +787,87 @@ mod tests {
         assert_eq!(result, expected);
         Ok(())
     }
+
+    #[test]
+    fn test_merge_respects_peer_options() -> Result<()> {
+        // Setup incoming database.
+        // root_incoming -> peer_1 -> peer_2
+        // root_incoming -> peer_3
+        let mut incoming_db = rusqlite::Connection::open_in_memory()?;
+        let incoming_tx = StoreTransaction::new(incoming_db.transaction()?)?;
+        setup(&incoming_tx)?;
+        let mut incoming_root_peer = get_root(&incoming_tx)?.unwrap();
+        let mut peer_1 = insert(
+            "peer_1",
+            &crate::common::GitUrl::try_from("https://localhost/peer_1")?,
+            Some(&mut incoming_root_peer),
+            &incoming_tx,
+        )?;
+        insert(
+            "peer_2",
+            &crate::common::GitUrl::try_from("https://localhost/peer_2")?,
+            Some(&mut peer_1),
+            &incoming_tx,
+        )?;
+        insert(
+            "peer_3",
+            &crate::common::GitUrl::try_from("https://localhost/peer_3")?,
+            Some(&mut incoming_root_peer),
+            &incoming_tx,
+        )?;
+
+        let mut db = rusqlite::Connection::open_in_memory()?;
+        let tx = StoreTransaction::new(db.transaction()?)?;
+        setup(&tx)?;
+
+        let options = crate::common::config::PeerOptions {
+            max_depth: Some(1),
+            deny_peers: maplit::btreeset! {"https://localhost/peer_3".to_string()},
+            ..Default::default()
+        };
+        let incoming_root_git_url =
+            crate::common::GitUrl::try_from("https://localhost/root_incoming")?;
+        let result = merge(
+            &incoming_root_git_url,
+            &options,
+            &Default::default(),
+            &incoming_tx,
+            &tx,
+        )?;
+
+        let result = result
+            .into_iter()
+            .map(|peer| peer.git_url.to_string())
+            .collect::<BTreeSet<_>>();
+        let expected = maplit::btreeset! {
+            "https://localhost/root_incoming".to_string(),
+            "https://localhost/peer_1".to_string(),
+        };
+        assert_eq!(result, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_by_alias_or_git_url() -> Result<()> {
+        let mut db = rusqlite::Connection::open_in_memory()?;
+        let tx = StoreTransaction::new(db.transaction()?)?;
+        setup(&tx)?;
+        let mut root_peer = get_root(&tx)?.unwrap();
+
+        let git_url = crate::common::GitUrl::try_from("https://github.com/user_1/reviews")?;
+        let alias = get_new_alias(&git_url, &tx)?;
+        assert_eq!(alias, "user_1");
+        let peer = insert(&alias, &git_url, Some(&mut root_peer), &tx)?;
+
+        assert_eq!(get_by_alias_or_git_url("user_1", &tx)?, Some(peer.clone()));
+        assert_eq!(
+            get_by_alias_or_git_url("https://github.com/user_1/reviews", &tx)?,
+            Some(peer)
+        );
+        assert_eq!(get_by_alias_or_git_url("user_2", &tx)?, None);
+
+        let git_url = crate::common::GitUrl::try_from("file:///tmp/user_3/reviews.git")?;
+        assert_eq!(get_new_alias(&git_url, &tx)?, "reviews");
+        Ok(())
+    }
 }
@@ -87,16 +87,21 @@ pub fn get(fields: &Fields, tx: &StoreTransaction) -> Result<HashSet<common::Reg
     ])?;
     let mut registries = HashSet::new();
     while let Some(row) = rows.next()? {
-        registries.insert(common::Registry {
-            id: row.get(0)?,
-            host_name: row.get(1)?,
-            human_url: url::Url::parse(row.get::<_, String>(2)?.as_str())?,
-            artifact_url: url::Url::parse(row.get::<_, String>(3)?.as_str())?,
-        });
+        registries.insert(get_from_row(&row, 0)?);
     }
     Ok(registries)
 }
 
+/// Parse registry from table columns, starting at the given column.
+pub fn get_from_row(row: &rusqlite::Row<'_>, start_column: usize) -> Result<common::Registry> {
+    Ok(common::Registry {
+        id: row.get(start_column)?,
+        host_name: row.get(start_column + 1)?,
+        human_url: url::Url::parse(row.get::<_, String>(start_column + 2)?.as_str())?,
+        artifact_url: url::Url::parse(row.get::<_, String>(start_column + 3)?.as_str())?,
+    })
+}
+
 /// Merge registries from incoming index into another index. Returns the newly merged registries.
 pub fn merge(
     incoming_tx: &StoreTransaction,
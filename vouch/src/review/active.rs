@@ -3,17 +3,44 @@ use anyhow::{Context, Result};
 use std::io::Write;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
-struct ActiveReview {
+pub struct ActiveReview {
     pub title: String,
     pub description: String,
     #[serde(rename = "isPrimary")]
     pub is_primary: Option<bool>,
     pub comments: std::collections::BTreeSet<review::comment::Comment>,
+
+    /// Review criteria checklist. Criteria set to true are certified by the reviewer.
+    #[serde(default)]
+    pub criteria: std::collections::BTreeMap<String, bool>,
+
+    /// Overall package verdict: pass, warn or fail.
+    #[serde(rename = "packageSecurity", default)]
+    pub package_security: Option<review::Summary>,
+
+    /// Reviewer confidence: low, medium or high.
+    #[serde(rename = "reviewConfidence", default)]
+    pub review_confidence: Option<review::Confidence>,
+}
+
+impl ActiveReview {
+    /// Returns the names of the criteria certified by the reviewer.
+    pub fn certified_criteria(&self) -> std::collections::BTreeSet<String> {
+        self.criteria
+            .iter()
+            .filter(|(_name, certified)| **certified)
+            .map(|(name, _certified)| name.clone())
+            .collect()
+    }
 }
 
 /// Ensure active review file is in place.
+///
+/// The criteria checklist holds the given criteria and any criteria already certified by the
+/// review.
 pub fn ensure(
     review: &review::Review,
+    criteria_names: &std::collections::BTreeSet<String>,
     reviews_directory: &std::path::PathBuf,
 ) -> Result<std::path::PathBuf> {
     let review_file_path = reviews_directory.join("local.review");
@@ -29,6 +56,13 @@ pub fn ensure(
         ),
         is_primary: Some(true),
         comments: review.comments.clone(),
+        criteria: criteria_names
+            .iter()
+            .chain(review.criteria.iter())
+            .map(|name| (name.clone(), review.criteria.contains(name)))
+            .collect(),
+        package_security: review.package_security.clone(),
+        review_confidence: review.review_confidence,
     };
 
     let mut file = std::fs::OpenOptions::new()
@@ -44,12 +78,10 @@ pub fn ensure(
     Ok(review_file_path)
 }
 
-pub fn parse(
-    path: &std::path::PathBuf,
-) -> Result<std::collections::BTreeSet<review::comment::Comment>> {
+pub fn parse(path: &std::path::PathBuf) -> Result<ActiveReview> {
     let file = std::fs::File::open(path)?;
     let reader = std::io::BufReader::new(file);
 
-    let active_review: review::active::ActiveReview = serde_json::from_reader(reader)?;
-    Ok(active_review.comments)
+    let active_review: ActiveReview = serde_json::from_reader(reader)?;
+    Ok(active_review)
 }
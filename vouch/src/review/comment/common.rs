@@ -27,6 +27,22 @@ pub struct Comment {
     #[serde(rename = "description")]
     pub message: String,
     pub selection: Option<Selection>,
+
+    /// Content hash of the comment which this comment replies to.
+    #[serde(rename = "inReplyTo", default, skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>,
+}
+
+impl crate::common::ContentHash for Comment {
+    fn content_hash(&self) -> String {
+        crate::common::get_content_hash(&serde_json::json!({
+            "summary": self.summary,
+            "path": self.path,
+            "message": self.message,
+            "selection": self.selection,
+            "in_reply_to": self.in_reply_to,
+        }))
+    }
 }
 
 impl Ord for Comment {
@@ -36,6 +52,7 @@ impl Ord for Comment {
             &self.path,
             &self.message,
             &self.selection,
+            &self.in_reply_to,
             &self.id,
         )
             .cmp(&(
@@ -43,6 +60,7 @@ impl Ord for Comment {
                 &other.path,
                 &other.message,
                 &other.selection,
+                &other.in_reply_to,
                 &other.id,
             ))
     }
@@ -60,6 +78,7 @@ impl crate::common::HashSansId for Comment {
         self.summary.hash(state);
         self.message.hash(state);
         self.selection.hash(state);
+        self.in_reply_to.hash(state);
     }
 }
 
@@ -72,3 +91,27 @@ impl crate::common::index::Identify for Comment {
         &mut self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::ContentHash;
+
+    #[test]
+    fn test_content_hash_ignores_id() {
+        let comment = Comment {
+            id: 1,
+            summary: Summary::Fail,
+            path: std::path::PathBuf::from("setup.py"),
+            message: "Downloads and runs a remote script.".to_string(),
+            selection: None,
+            in_reply_to: None,
+        };
+        let mut other = comment.clone();
+        other.id = 2;
+        assert_eq!(comment.content_hash(), other.content_hash());
+
+        other.in_reply_to = Some(comment.content_hash());
+        assert_ne!(comment.content_hash(), other.content_hash());
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{format_err, Result};
 
 use super::common;
-use crate::common::StoreTransaction;
+use crate::common::{ContentHash, StoreTransaction};
 
 pub fn setup(tx: &StoreTransaction) -> Result<()> {
     tx.index_tx().execute(
@@ -14,10 +14,16 @@ pub fn setup(tx: &StoreTransaction) -> Result<()> {
             selection_start_line      INTEGER,
             selection_start_character INTEGER,
             selection_end_line        INTEGER,
-            selection_end_character   INTEGER
+            selection_end_character   INTEGER,
+            in_reply_to               TEXT,
+            content_hash              TEXT
         )",
         rusqlite::NO_PARAMS,
     )?;
+    tx.index_tx().execute(
+        "CREATE INDEX IF NOT EXISTS comment_content_hash_index ON comment(content_hash)",
+        rusqlite::NO_PARAMS,
+    )?;
     Ok(())
 }
 
@@ -27,8 +33,18 @@ pub fn insert(
     summary: &crate::review::common::Summary,
     message: &str,
     selection: &Option<common::Selection>,
+    in_reply_to: &Option<String>,
     tx: &StoreTransaction,
 ) -> Result<common::Comment> {
+    let mut comment = common::Comment {
+        id: 0,
+        path: path.clone(),
+        summary: summary.clone(),
+        message: message.to_string(),
+        selection: selection.clone(),
+        in_reply_to: in_reply_to.clone(),
+    };
+
     tx.index_tx().execute_named(
         r"
             INSERT INTO comment (
@@ -38,7 +54,9 @@ pub fn insert(
                 selection_start_line,
                 selection_start_character,
                 selection_end_line,
-                selection_end_character
+                selection_end_character,
+                in_reply_to,
+                content_hash
             )
             VALUES (
                 :path,
@@ -47,7 +65,9 @@ pub fn insert(
                 :selection_start_line,
                 :selection_start_character,
                 :selection_end_line,
-                :selection_end_character
+                :selection_end_character,
+                :in_reply_to,
+                :content_hash
             )
         ",
         &[
@@ -75,62 +95,53 @@ pub fn insert(
                 ":selection_end_character",
                 &selection.clone().map(|s| s.end.character),
             ),
+            (":in_reply_to", in_reply_to),
+            (":content_hash", &comment.content_hash()),
         ],
     )?;
-    Ok(common::Comment {
-        id: tx.index_tx().last_insert_rowid(),
-        path: path.clone(),
-        summary: summary.clone(),
-        message: message.to_string(),
-        selection: selection.clone(),
-    })
+    comment.id = tx.index_tx().last_insert_rowid();
+    Ok(comment)
 }
 
 #[derive(Debug, Default)]
-pub struct Fields<'a> {
+pub struct Fields {
     pub id: Option<crate::common::index::ID>,
-    pub ids: Option<&'a Vec<crate::common::index::ID>>,
 }
 
-/// Get matching comments.
-pub fn get(
-    fields: &Fields,
-    tx: &StoreTransaction,
-) -> Result<std::collections::HashSet<common::Comment>> {
-    let ids_where_field = crate::common::index::get_ids_where_field(&fields.ids);
+/// Comment table columns selected by `get_from_row`.
+pub static SELECT_COLUMNS: &str = r"
+            comment.id,
+            comment.path,
+            comment.summary,
+            comment.message,
+            comment.selection_start_line,
+            comment.selection_start_character,
+            comment.selection_end_line,
+            comment.selection_end_character,
+            comment.in_reply_to";
 
-    let sql_query = format!(
-        "
-        SELECT *
-        FROM comment
-        WHERE
-            {ids_where_field}
-    ",
-        ids_where_field = ids_where_field
-    );
-    let mut statement = tx.index_tx().prepare(sql_query.as_str())?;
-    let mut rows = statement.query_named(&[])?;
-
-    let mut comments = std::collections::HashSet::new();
-    while let Some(row) = rows.next()? {
-        comments.insert(common::Comment {
-            id: row.get(0)?,
-            path: std::path::PathBuf::from(&row.get::<_, String>(1)?),
-            summary: row.get::<_, String>(2)?.parse()?,
-            message: row.get::<_, String>(3)?,
-            selection: get_selection_field(row)?,
-        });
-    }
-    Ok(comments)
+/// Parse comment from row columns selected using `SELECT_COLUMNS`, starting at the given column.
+pub fn get_from_row(row: &rusqlite::Row<'_>, start_column: usize) -> Result<common::Comment> {
+    Ok(common::Comment {
+        id: row.get(start_column)?,
+        path: std::path::PathBuf::from(&row.get::<_, String>(start_column + 1)?),
+        summary: row.get::<_, String>(start_column + 2)?.parse()?,
+        message: row.get::<_, String>(start_column + 3)?,
+        selection: get_selection_field(row, start_column + 4)?,
+        in_reply_to: row.get(start_column + 8)?,
+    })
 }
 
 /// Given a comment table row, return a comment selection.
-fn get_selection_field(row: &rusqlite::Row<'_>) -> Result<Option<common::Selection>> {
+fn get_selection_field(
+    row: &rusqlite::Row<'_>,
+    start_column: usize,
+) -> Result<Option<common::Selection>> {
     let selection_fields = [
-        row.get::<_, Option<i64>>(4)?, // Start line.
-        row.get::<_, Option<i64>>(5)?, // Start character.
-        row.get::<_, Option<i64>>(6)?, // End line.
-        row.get::<_, Option<i64>>(7)?, // End character.
+        row.get::<_, Option<i64>>(start_column)?,     // Start line.
+        row.get::<_, Option<i64>>(start_column + 1)?, // Start character.
+        row.get::<_, Option<i64>>(start_column + 2)?, // End line.
+        row.get::<_, Option<i64>>(start_column + 3)?, // End character.
     ];
 
     let all_fields_none = selection_fields
@@ -0,0 +1,31 @@
+/// Reviewer confidence in, or thoroughness of, a package review.
+#[derive(
+    Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::str::FromStr for Confidence {
+    type Err = anyhow::Error;
+    fn from_str(input: &str) -> Result<Confidence, Self::Err> {
+        match input {
+            "low" => Ok(Confidence::Low),
+            "medium" => Ok(Confidence::Medium),
+            "high" => Ok(Confidence::High),
+            _ => Err(anyhow::format_err!(
+                "Failed to parse confidence from string: {}",
+                input
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
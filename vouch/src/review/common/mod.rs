@@ -9,7 +9,9 @@
 use serde;
 use std::hash::Hash;
 
+pub mod confidence;
 pub mod summary;
+pub use confidence::Confidence;
 pub use summary::Summary;
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -20,16 +22,39 @@ pub struct Review {
     pub peer: crate::peer::Peer,
     pub package: crate::package::Package,
     pub comments: std::collections::BTreeSet<crate::review::comment::Comment>,
+
+    /// Names of the review criteria which the reviewer certifies the package as meeting.
+    #[serde(default)]
+    pub criteria: std::collections::BTreeSet<String>,
+
+    /// Reviewer's overall verdict on the package. Takes precedence over comment summaries.
+    #[serde(default)]
+    pub package_security: Option<Summary>,
+
+    #[serde(default)]
+    pub review_confidence: Option<Confidence>,
 }
 
 impl Ord for Review {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        (&self.peer, &self.package, &self.comments, &self.id).cmp(&(
-            &other.peer,
-            &other.package,
-            &other.comments,
-            &other.id,
-        ))
+        (
+            &self.peer,
+            &self.package,
+            &self.comments,
+            &self.criteria,
+            &self.package_security,
+            &self.review_confidence,
+            &self.id,
+        )
+            .cmp(&(
+                &other.peer,
+                &other.package,
+                &other.comments,
+                &other.criteria,
+                &other.package_security,
+                &other.review_confidence,
+                &other.id,
+            ))
     }
 }
 
@@ -38,3 +63,34 @@ impl PartialOrd for Review {
         Some(self.cmp(other))
     }
 }
+
+impl crate::common::ContentHash for Review {
+    /// Review content excludes the authoring peer, which is recorded relative to each index.
+    fn content_hash(&self) -> String {
+        get_content_hash(
+            &self.package.content_hash(),
+            self.comments.iter().map(|comment| comment.content_hash()),
+            &self.criteria,
+            &self.package_security,
+            &self.review_confidence,
+        )
+    }
+}
+
+/// Returns a review content hash from the content hashes of its package and comments.
+pub fn get_content_hash(
+    package_hash: &str,
+    comment_hashes: impl Iterator<Item = String>,
+    criteria: &std::collections::BTreeSet<String>,
+    package_security: &Option<Summary>,
+    review_confidence: &Option<Confidence>,
+) -> String {
+    let comment_hashes = comment_hashes.collect::<std::collections::BTreeSet<_>>();
+    crate::common::get_content_hash(&serde_json::json!({
+        "package": package_hash,
+        "comments": comment_hashes,
+        "criteria": criteria,
+        "package_security": package_security,
+        "review_confidence": review_confidence,
+    }))
+}
@@ -66,3 +66,94 @@ pub fn add(review: &review::Review) -> Result<()> {
     file.write_all(serde_json::to_string_pretty(&review)?.as_bytes())?;
     Ok(())
 }
+
+/// Returns all reviews stored within a given reviews directory.
+///
+/// Hidden directories, such as the ongoing reviews directory, are skipped.
+pub fn get_all(reviews_directory: &std::path::PathBuf) -> Result<Vec<review::Review>> {
+    let mut reviews = Vec::new();
+    if !reviews_directory.is_dir() {
+        return Ok(reviews);
+    }
+
+    let mut unprocessed_directories = vec![reviews_directory.clone()];
+    while let Some(directory) = unprocessed_directories.pop() {
+        for entry in std::fs::read_dir(&directory)? {
+            let path = entry?.path();
+            let is_hidden = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with("."))
+                .unwrap_or(false);
+
+            if path.is_dir() && !is_hidden {
+                unprocessed_directories.push(path);
+            } else if path.is_file()
+                && path.file_name() == Some(std::ffi::OsStr::new(REVIEW_FILE_NAME))
+            {
+                let file = std::fs::File::open(&path)?;
+                let reader = std::io::BufReader::new(file);
+                let review: review::Review = serde_json::from_reader(reader)
+                    .context(format!("Failed to parse review file: {}", path.display()))?;
+                reviews.push(review);
+            }
+        }
+    }
+    Ok(reviews)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_review(package_name: &str) -> Result<review::Review> {
+        Ok(review::Review {
+            id: 0,
+            peer: crate::peer::Peer::default(),
+            package: crate::package::Package {
+                id: 0,
+                name: package_name.to_string(),
+                version: "1.0.0".to_string(),
+                registries: maplit::btreeset! {crate::registry::Registry {
+                    id: 0,
+                    host_name: "pypi.org".to_string(),
+                    human_url: url::Url::parse("https://pypi.org/project/test/1.0.0/")?,
+                    artifact_url: url::Url::parse("https://localhost/test-1.0.0.tar.gz")?,
+                }},
+                artifact_hash: "artifact_hash".to_string(),
+            },
+            comments: std::collections::BTreeSet::new(),
+            criteria: std::collections::BTreeSet::new(),
+            package_security: None,
+            review_confidence: None,
+        })
+    }
+
+    #[test]
+    fn test_get_all_skips_ongoing_reviews() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("vouch_test_review_fs")?;
+        let reviews_directory = tmp_dir.path().to_path_buf();
+
+        for (directory, review) in vec![
+            (
+                reviews_directory.join("pypi.org/package_1/1.0.0"),
+                get_review("package_1")?,
+            ),
+            (
+                reviews_directory.join(".ongoing/pypi.org/package_2/1.0.0"),
+                get_review("package_2")?,
+            ),
+        ] {
+            std::fs::create_dir_all(&directory)?;
+            std::fs::write(
+                directory.join(REVIEW_FILE_NAME),
+                serde_json::to_string_pretty(&review)?,
+            )?;
+        }
+
+        let result = get_all(&reviews_directory)?;
+        let expected = vec![get_review("package_1")?];
+        assert_eq!(result, expected);
+        Ok(())
+    }
+}
@@ -1,10 +1,10 @@
 use anyhow::{format_err, Result};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use super::comment;
 use super::common;
-use crate::common::StoreTransaction;
+use crate::common::{ContentHash, StoreTransaction};
 use crate::package;
 use crate::peer;
 
@@ -14,11 +14,12 @@ pub struct Fields<'a> {
     pub peer: Option<&'a peer::Peer>,
     pub package_id: Option<crate::common::index::ID>,
 
-    pub package_security: Option<crate::common::index::ID>,
-    pub review_confidence: Option<crate::common::index::ID>,
+    pub package_security: Option<common::Summary>,
+    pub review_confidence: Option<common::Confidence>,
 
     pub package_name: Option<&'a str>,
     pub package_version: Option<&'a str>,
+    pub content_hash: Option<&'a str>,
 
     // Filters match for any in set.
     pub registry_host_names: Option<std::collections::BTreeSet<&'a str>>,
@@ -33,7 +34,9 @@ pub fn setup(tx: &StoreTransaction) -> Result<()> {
             id                    INTEGER NOT NULL PRIMARY KEY,
             peer_id               INTEGER NOT NULL,
             package_id            INTEGER NOT NULL,
-            comment_ids           BLOB,
+            package_security      TEXT,
+            review_confidence     TEXT,
+            content_hash          TEXT,
 
             UNIQUE(peer_id, package_id)
             FOREIGN KEY(peer_id) REFERENCES peer(id)
@@ -44,20 +47,61 @@ pub fn setup(tx: &StoreTransaction) -> Result<()> {
         )",
         rusqlite::NO_PARAMS,
     )?;
+    tx.index_tx().execute(
+        "CREATE INDEX IF NOT EXISTS review_package_id_index ON review(package_id)",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        "CREATE INDEX IF NOT EXISTS review_content_hash_index ON review(content_hash)",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        r"
+        CREATE TABLE IF NOT EXISTS review_comment (
+            review_id             INTEGER NOT NULL,
+            comment_id            INTEGER NOT NULL,
+
+            PRIMARY KEY(review_id, comment_id)
+            FOREIGN KEY(review_id) REFERENCES review(id) ON DELETE CASCADE
+            FOREIGN KEY(comment_id) REFERENCES comment(id) ON DELETE CASCADE
+        )",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        "CREATE INDEX IF NOT EXISTS review_comment_comment_id_index ON review_comment(comment_id)",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        r"
+        CREATE TABLE IF NOT EXISTS review_criterion (
+            review_id             INTEGER NOT NULL,
+            name                  TEXT NOT NULL,
+
+            PRIMARY KEY(review_id, name)
+            FOREIGN KEY(review_id) REFERENCES review(id) ON DELETE CASCADE
+        )",
+        rusqlite::NO_PARAMS,
+    )?;
     Ok(())
 }
 
 pub fn insert(
     comments: &std::collections::BTreeSet<comment::Comment>,
+    criteria: &std::collections::BTreeSet<String>,
+    package_security: &Option<common::Summary>,
+    review_confidence: &Option<common::Confidence>,
     peer: &crate::peer::Peer,
     package: &crate::package::Package,
     tx: &StoreTransaction,
 ) -> Result<common::Review> {
-    let comment_ids: Vec<crate::common::index::ID> = comments.into_iter().map(|c| c.id).collect();
-    let comment_ids = if !comment_ids.is_empty() {
-        Some(bincode::serialize(&comment_ids)?)
-    } else {
-        None
+    let mut review = common::Review {
+        id: 0,
+        peer: peer.clone(),
+        package: package.clone(),
+        comments: comments.clone(),
+        criteria: criteria.clone(),
+        package_security: package_security.clone(),
+        review_confidence: *review_confidence,
     };
 
     tx.index_tx().execute_named(
@@ -65,26 +109,84 @@ pub fn insert(
             INSERT INTO review (
                 peer_id,
                 package_id,
-                comment_ids
+                package_security,
+                review_confidence,
+                content_hash
             )
             VALUES (
                 :peer_id,
                 :package_id,
-                :comment_ids
+                :package_security,
+                :review_confidence,
+                :content_hash
             )
         ",
         &[
             (":peer_id", &peer.id),
             (":package_id", &package.id),
-            (":comment_ids", &comment_ids),
+            (
+                ":package_security",
+                &package_security.as_ref().map(|summary| summary.to_string()),
+            ),
+            (
+                ":review_confidence",
+                &review_confidence.map(|confidence| confidence.to_string()),
+            ),
+            (":content_hash", &review.content_hash()),
         ],
     )?;
-    Ok(common::Review {
-        id: tx.index_tx().last_insert_rowid(),
-        peer: peer.clone(),
-        package: package.clone(),
-        comments: comments.clone(),
-    })
+    review.id = tx.index_tx().last_insert_rowid();
+    insert_comment_links(review.id, &comments, &tx)?;
+    insert_criteria(review.id, &criteria, &tx)?;
+    Ok(review)
+}
+
+/// Link comments to a review.
+fn insert_comment_links(
+    review_id: crate::common::index::ID,
+    comments: &std::collections::BTreeSet<comment::Comment>,
+    tx: &StoreTransaction,
+) -> Result<()> {
+    for comment in comments {
+        tx.index_tx().execute_named(
+            r"
+                INSERT INTO review_comment (
+                    review_id,
+                    comment_id
+                )
+                VALUES (
+                    :review_id,
+                    :comment_id
+                )
+            ",
+            &[(":review_id", &review_id), (":comment_id", &comment.id)],
+        )?;
+    }
+    Ok(())
+}
+
+/// Record the criteria which a review certifies.
+fn insert_criteria(
+    review_id: crate::common::index::ID,
+    criteria: &std::collections::BTreeSet<String>,
+    tx: &StoreTransaction,
+) -> Result<()> {
+    for criterion in criteria {
+        tx.index_tx().execute_named(
+            r"
+                INSERT INTO review_criterion (
+                    review_id,
+                    name
+                )
+                VALUES (
+                    :review_id,
+                    :name
+                )
+            ",
+            &[(":review_id", &review_id), (":name", criterion)],
+        )?;
+    }
+    Ok(())
 }
 
 pub fn update(review: &common::Review, tx: &StoreTransaction) -> Result<()> {
@@ -96,7 +198,9 @@ pub fn update(review: &common::Review, tx: &StoreTransaction) -> Result<()> {
             SET
                 peer_id = :peer_id,
                 package_id = :package_id,
-                comment_ids = :comment_ids
+                package_security = :package_security,
+                review_confidence = :review_confidence,
+                content_hash = :content_hash
             WHERE
                 id = :id
         ",
@@ -105,11 +209,33 @@ pub fn update(review: &common::Review, tx: &StoreTransaction) -> Result<()> {
             (":peer_id", &review.peer.id),
             (":package_id", &review.package.id),
             (
-                ":comment_ids",
-                &bincode::serialize(&review.comments.iter().map(|c| c.id).collect::<Vec<_>>())?,
+                ":package_security",
+                &review
+                    .package_security
+                    .as_ref()
+                    .map(|summary| summary.to_string()),
             ),
+            (
+                ":review_confidence",
+                &review
+                    .review_confidence
+                    .map(|confidence| confidence.to_string()),
+            ),
+            (":content_hash", &review.content_hash()),
         ],
     )?;
+
+    tx.index_tx().execute_named(
+        "DELETE FROM review_comment WHERE review_id = :review_id",
+        &[(":review_id", &review.id)],
+    )?;
+    insert_comment_links(review.id, &review.comments, &tx)?;
+
+    tx.index_tx().execute_named(
+        "DELETE FROM review_criterion WHERE review_id = :review_id",
+        &[(":review_id", &review.id)],
+    )?;
+    insert_criteria(review.id, &review.criteria, &tx)?;
     Ok(())
 }
 
@@ -160,108 +286,295 @@ pub fn get(fields: &Fields, tx: &StoreTransaction) -> Result<Vec<common::Review>
     let peer_id = crate::common::index::get_like_clause_param(
         fields.peer.map(|peer| peer.id.to_string()).as_deref(),
     );
-
-    let mut statement = tx.index_tx().prepare(
+    let package_security = fields
+        .package_security
+        .as_ref()
+        .map(|summary| summary.to_string());
+    let review_confidence = fields
+        .review_confidence
+        .map(|confidence| confidence.to_string());
+    let (registry_host_names_clause, registry_host_names_parameters) =
+        package::index::get_registry_host_names_clause("package.id", &fields.registry_host_names);
+
+    // Nullable columns are matched exactly when a filter is given.
+    let where_clause = format!(
         r"
-        SELECT
-            review.id,
-            peer.id,
-            package.id,
-            review.comment_ids
-        FROM review
-        JOIN peer
-            ON review.peer_id = peer.id
-        JOIN package
-            ON review.package_id = package.id
-        WHERE
             review.id LIKE :review_id ESCAPE '\'
             AND package.name LIKE :name ESCAPE '\'
             AND package.version LIKE :version ESCAPE '\'
             AND peer.id LIKE :peer_id ESCAPE '\'
+            AND (:package_security IS NULL OR review.package_security = :package_security)
+            AND (:review_confidence IS NULL OR review.review_confidence = :review_confidence)
+            AND (:content_hash IS NULL OR review.content_hash = :content_hash)
+            AND {registry_host_names_clause}
         ",
-    )?;
-    let mut rows = statement.query_named(&[
+        registry_host_names_clause = registry_host_names_clause
+    );
+    let mut parameters: Vec<(&str, &dyn rusqlite::ToSql)> = vec![
         (":review_id", &review_id),
         (":name", &package_name),
         (":version", &package_version),
         (":peer_id", &peer_id),
-    ])?;
+        (":package_security", &package_security),
+        (":review_confidence", &review_confidence),
+        (":content_hash", &fields.content_hash),
+    ];
+    for (name, value) in &registry_host_names_parameters {
+        parameters.push((name.as_str(), value));
+    }
+    query(&where_clause, &parameters, &tx)
+}
 
-    let mut reviews = Vec::new();
-    while let Some(row) = rows.next()? {
-        let peer = peer::index::get(
-            &peer::index::Fields {
-                id: row.get(1)?,
-                ..Default::default()
-            },
-            &tx,
-        )?
-        .into_iter()
-        .next()
-        .ok_or(format_err!("Failed to find review peer in index."))?;
+/// Maximum number of packages matched per query by `get_by_packages` and `get_by_package_names`.
+///
+/// Keeps the number of bound parameters below the SQLite limit.
+static PACKAGES_BATCH_SIZE: usize = 400;
+
+/// Maximum number of reviews per criteria and comments query made by `query`.
+static REVIEW_IDS_BATCH_SIZE: usize = 500;
+
+/// Returns reviews for many packages from a single registry using a bounded number of queries.
+///
+/// Reviews are grouped by the given (package name, package version) pairs. Matching is case
+/// insensitive. Every given pair has an entry in the returned map.
+pub fn get_by_packages(
+    packages: &std::collections::BTreeSet<(&str, &str)>,
+    registry_host_name: &str,
+    tx: &StoreTransaction,
+) -> Result<std::collections::BTreeMap<(String, String), Vec<common::Review>>> {
+    let get_key = |name: &str, version: &str| (name.to_lowercase(), version.to_lowercase());
+
+    let keys = packages
+        .iter()
+        .map(|(name, version)| vec![*name, *version])
+        .collect::<Vec<_>>();
+    let mut found_reviews = HashMap::<(String, String), Vec<common::Review>>::new();
+    for review in query_batched(
+        &["package.name", "package.version"],
+        &keys,
+        registry_host_name,
+        &tx,
+    )? {
+        found_reviews
+            .entry(get_key(&review.package.name, &review.package.version))
+            .or_default()
+            .push(review);
+    }
 
-        let package = package::index::get(
-            &package::index::Fields {
-                id: row.get(2)?,
-                ..Default::default()
-            },
-            &tx,
-        )?
-        .into_iter()
-        .next()
-        .ok_or(format_err!("Failed to find review package in index."))?;
+    Ok(packages
+        .iter()
+        .map(|(name, version)| {
+            let reviews = found_reviews
+                .get(&get_key(name, version))
+                .cloned()
+                .unwrap_or_default();
+            ((name.to_string(), version.to_string()), reviews)
+        })
+        .collect())
+}
 
-        // Skip review if associated package registries do not match on given registry host names.
-        if let Some(registry_host_names) = &fields.registry_host_names {
-            if !package
-                .registries
-                .iter()
-                .any(|registry| registry_host_names.contains(registry.host_name.as_str()))
-            {
-                continue;
+/// Returns reviews of any version of many packages from a single registry.
+///
+/// Reviews are grouped by the given package names. Matching is case insensitive. Every given
+/// package name has an entry in the returned map.
+pub fn get_by_package_names(
+    package_names: &std::collections::BTreeSet<&str>,
+    registry_host_name: &str,
+    tx: &StoreTransaction,
+) -> Result<std::collections::BTreeMap<String, Vec<common::Review>>> {
+    let keys = package_names
+        .iter()
+        .map(|name| vec![*name])
+        .collect::<Vec<_>>();
+    let mut found_reviews = HashMap::<String, Vec<common::Review>>::new();
+    for review in query_batched(&["package.name"], &keys, registry_host_name, &tx)? {
+        found_reviews
+            .entry(review.package.name.to_lowercase())
+            .or_default()
+            .push(review);
+    }
+
+    Ok(package_names
+        .iter()
+        .map(|name| {
+            let reviews = found_reviews
+                .get(&name.to_lowercase())
+                .cloned()
+                .unwrap_or_default();
+            (name.to_string(), reviews)
+        })
+        .collect())
+}
+
+/// Query reviews from a single registry where the given columns match any of the given keys.
+///
+/// Each key holds one value per column. Matching is case insensitive. Keys are queried in batches.
+fn query_batched(
+    columns: &[&str],
+    keys: &[Vec<&str>],
+    registry_host_name: &str,
+    tx: &StoreTransaction,
+) -> Result<Vec<common::Review>> {
+    let (registry_host_names_clause, registry_host_names_parameters) =
+        package::index::get_registry_host_names_clause(
+            "package.id",
+            &Some(maplit::btreeset! {registry_host_name}),
+        );
+    let columns_clause = columns
+        .iter()
+        .map(|column| format!("lower({})", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut reviews = Vec::new();
+    for keys_batch in keys.chunks(PACKAGES_BATCH_SIZE) {
+        let mut key_parameters = Vec::new();
+        let mut key_values = Vec::new();
+        for (key_index, key) in keys_batch.iter().enumerate() {
+            let mut value_parameters = Vec::new();
+            for (value_index, value) in key.iter().enumerate() {
+                let parameter = format!(":key_{}_{}", key_index, value_index);
+                value_parameters.push(format!("lower({})", parameter));
+                key_parameters.push((parameter, value.to_string()));
             }
+            key_values.push(format!("({})", value_parameters.join(", ")));
         }
 
-        let comment_ids: Option<Result<Vec<crate::common::index::ID>>> = row
-            .get::<_, Option<Vec<u8>>>(3)?
-            .map(|x| Ok(bincode::deserialize(&x)?));
-        let comments = match comment_ids {
-            Some(comment_ids) => {
-                let comment_ids = comment_ids?;
-                comment::index::get(
-                    &comment::index::Fields {
-                        ids: Some(&comment_ids),
-                        ..Default::default()
+        let where_clause = format!(
+            r"
+                ({columns_clause}) IN (VALUES {key_values})
+                AND {registry_host_names_clause}
+            ",
+            columns_clause = columns_clause,
+            key_values = key_values.join(", "),
+            registry_host_names_clause = registry_host_names_clause
+        );
+        let parameters: Vec<(&str, &dyn rusqlite::ToSql)> = key_parameters
+            .iter()
+            .chain(registry_host_names_parameters.iter())
+            .map(|(name, value)| (name.as_str(), value as &dyn rusqlite::ToSql))
+            .collect();
+        reviews.extend(query(&where_clause, &parameters, &tx)?);
+    }
+    Ok(reviews)
+}
+
+/// Query reviews matching the given where clause. Each review is loaded in full.
+fn query(
+    where_clause: &str,
+    parameters: &[(&str, &dyn rusqlite::ToSql)],
+    tx: &StoreTransaction,
+) -> Result<Vec<common::Review>> {
+    // Rows are produced per review package registry.
+    let sql_query = format!(
+        r"
+        SELECT
+            review.id,
+            review.package_security,
+            review.review_confidence,
+            {peer_columns},
+            {package_columns}
+        FROM review
+        JOIN peer
+            ON review.peer_id = peer.id
+        JOIN package
+            ON review.package_id = package.id
+        {join_registry}
+        WHERE
+            {where_clause}
+        ",
+        peer_columns = peer::index::SELECT_COLUMNS,
+        package_columns = package::index::SELECT_COLUMNS,
+        join_registry = package::index::JOIN_REGISTRY,
+        where_clause = where_clause
+    );
+    let mut statement = tx.index_tx().prepare(&sql_query)?;
+    let mut rows = statement.query_named(&parameters)?;
+
+    let peer_start_column = 3;
+    let package_start_column = peer_start_column + 5;
+
+    let mut reviews = std::collections::BTreeMap::<crate::common::index::ID, common::Review>::new();
+    while let Some(row) = rows.next()? {
+        let review_id: crate::common::index::ID = row.get(0)?;
+        let package = package::index::get_from_row(&row, package_start_column)?;
+
+        match reviews.get_mut(&review_id) {
+            Some(review) => {
+                review.package.registries.extend(package.registries);
+            }
+            None => {
+                let review = common::Review {
+                    id: review_id,
+                    peer: peer::index::get_from_row(&row, peer_start_column)?,
+                    package,
+                    comments: std::collections::BTreeSet::new(),
+                    criteria: std::collections::BTreeSet::new(),
+                    package_security: match row.get::<_, Option<String>>(1)? {
+                        Some(package_security) => Some(package_security.parse()?),
+                        None => None,
                     },
-                    &tx,
-                )?
-                .into_iter()
-                .collect()
+                    review_confidence: match row.get::<_, Option<String>>(2)? {
+                        Some(review_confidence) => Some(review_confidence.parse()?),
+                        None => None,
+                    },
+                };
+                reviews.insert(review_id, review);
             }
-            None => std::collections::BTreeSet::<comment::Comment>::new(),
-        };
+        }
+    }
 
-        let review = common::Review {
-            id: row.get(0)?,
-            peer,
-            package,
-            comments,
-        };
-        reviews.push(review);
+    let review_ids = reviews.keys().cloned().collect::<Vec<_>>();
+    for review_ids_batch in review_ids.chunks(REVIEW_IDS_BATCH_SIZE) {
+        let review_ids_clause = review_ids_batch
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut statement = tx.index_tx().prepare(&format!(
+            r"
+            SELECT review_id, name
+            FROM review_criterion
+            WHERE review_id IN ({review_ids})
+            ",
+            review_ids = review_ids_clause
+        ))?;
+        let mut rows = statement.query(rusqlite::NO_PARAMS)?;
+        while let Some(row) = rows.next()? {
+            let review_id: crate::common::index::ID = row.get(0)?;
+            if let Some(review) = reviews.get_mut(&review_id) {
+                review.criteria.insert(row.get(1)?);
+            }
+        }
+
+        let mut statement = tx.index_tx().prepare(&format!(
+            r"
+            SELECT
+                review_comment.review_id,
+                {comment_columns}
+            FROM review_comment
+            JOIN comment
+                ON comment.id = review_comment.comment_id
+            WHERE review_comment.review_id IN ({review_ids})
+            ",
+            comment_columns = comment::index::SELECT_COLUMNS,
+            review_ids = review_ids_clause
+        ))?;
+        let mut rows = statement.query(rusqlite::NO_PARAMS)?;
+        while let Some(row) = rows.next()? {
+            let review_id: crate::common::index::ID = row.get(0)?;
+            if let Some(review) = reviews.get_mut(&review_id) {
+                review
+                    .comments
+                    .insert(comment::index::get_from_row(&row, 1)?);
+            }
+        }
     }
-    Ok(reviews)
+    Ok(reviews.into_iter().map(|(_id, review)| review).collect())
 }
 
 pub fn remove(fields: &Fields, tx: &StoreTransaction) -> Result<()> {
-    let id =
-        crate::common::index::get_like_clause_param(fields.id.map(|id| id.to_string()).as_deref());
-    let package_name = crate::common::index::get_like_clause_param(fields.package_name);
-    let package_version = crate::common::index::get_like_clause_param(fields.package_version);
-
-    let peer_id = crate::common::index::get_like_clause_param(
-        fields.peer.map(|peer| peer.id.to_string()).as_deref(),
-    );
-
     for review in get(&fields, &tx)? {
         // Remove package.
         package::index::remove(
@@ -282,43 +595,48 @@ pub fn remove(fields: &Fields, tx: &StoreTransaction) -> Result<()> {
                 &tx,
             )?;
         }
-    }
 
-    tx.index_tx().execute_named(
-        r"
-        DELETE FROM review
-        WHERE review.id IN (
-            SELECT review.id
-            FROM review
-            JOIN peer
-                ON review.peer_id = peer.id
-            JOIN package
-                ON review.package_id = package.id
-            WHERE
-                review.id LIKE :id ESCAPE '\'
-                AND package.name LIKE :name ESCAPE '\'
-                AND package.version LIKE :version ESCAPE '\'
-                AND peer.id LIKE :peer_id ESCAPE '\'
-        )
-        ",
-        &[
-            (":id", &id),
-            (":name", &package_name),
-            (":version", &package_version),
-            (":peer_id", &peer_id),
-        ],
-    )?;
+        tx.index_tx().execute_named(
+            "DELETE FROM review_comment WHERE review_id = :review_id",
+            &[(":review_id", &review.id)],
+        )?;
+        tx.index_tx().execute_named(
+            "DELETE FROM review_criterion WHERE review_id = :review_id",
+            &[(":review_id", &review.id)],
+        )?;
+        tx.index_tx().execute_named(
+            "DELETE FROM review WHERE id = :review_id",
+            &[(":review_id", &review.id)],
+        )?;
+    }
     Ok(())
 }
 
-/// Merge reviews from incoming index into another index. Returns the newly merged reviews.
+/// Merge reviews from incoming index into another index. Returns the newly merged or updated
+/// reviews.
+///
+/// Incoming reviews are matched to existing reviews by originating peer git URL and content hash.
+/// The same review may be reached through several peers which follow its author. An existing
+/// review of the same package by the same peer is updated whenever the incoming copy differs.
 pub fn merge(
     incoming_root_git_url: &crate::common::GitUrl,
+    options: &crate::common::config::PeerOptions,
+    blocklist: &crate::common::config::Blocklist,
     incoming_tx: &StoreTransaction,
     tx: &StoreTransaction,
 ) -> Result<HashSet<common::Review>> {
     let incoming_reviews = get(&Fields::default(), &incoming_tx)?;
 
+    // Depth of each incoming peer below the incoming root peer.
+    let incoming_root_peer = peer::index::get_root(&incoming_tx)?
+        .ok_or(format_err!("Cannot find incoming root peer."))?;
+    let incoming_peer_depths: HashMap<crate::common::index::ID, usize> =
+        peer::index::get_breadth_first_child_peers(&incoming_root_peer, &incoming_tx)?
+            .into_iter()
+            .enumerate()
+            .flat_map(|(depth, peers)| peers.into_iter().map(move |peer| (peer.id, depth)))
+            .collect();
+
     let mut new_reviews = HashSet::new();
     for review in incoming_reviews {
         let peer_git_url = if review.peer.is_root() {
@@ -327,7 +645,36 @@ pub fn merge(
             review.peer.git_url.clone()
         };
 
-        let peer = peer::index::get(
+        if blocklist.is_blocked(&peer_git_url) {
+            log::info!("Skipping review by blocked peer: {}", peer_git_url);
+            continue;
+        }
+        if !review.peer.is_root() {
+            let is_allowed = match incoming_peer_depths.get(&review.peer.id) {
+                Some(depth) => options.is_peer_allowed(&peer_git_url, *depth),
+                None => false,
+            };
+            if !is_allowed {
+                log::debug!("Skipping review by excluded peer: {}", peer_git_url);
+                continue;
+            }
+        }
+        if !options.is_registries_allowed(
+            review
+                .package
+                .registries
+                .iter()
+                .map(|registry| registry.host_name.as_str()),
+        ) {
+            log::debug!(
+                "Skipping review of package from excluded registry: {}",
+                review.package.name
+            );
+            continue;
+        }
+
+        // Peers excluded from the peer tree (e.g. beyond the maximum depth) are absent.
+        let peer = match peer::index::get(
             &peer::index::Fields {
                 git_url: Some(&peer_git_url),
                 ..Default::default()
@@ -336,23 +683,35 @@ pub fn merge(
         )?
         .into_iter()
         .next()
-        .ok_or(format_err!(
-            "Failed to find matching peer for review: {:?}",
-            review
-        ))?;
+        {
+            Some(peer) => peer,
+            None => {
+                log::debug!("Skipping review by peer not in index: {}", peer_git_url);
+                continue;
+            }
+        };
 
-        let registry_host_names = review
-            .package
-            .registries
+        let content_hash = review.content_hash();
+        let existing_reviews = get(
+            &Fields {
+                peer: Some(&peer),
+                package_name: Some(&review.package.name),
+                package_version: Some(&review.package.version),
+                ..Default::default()
+            },
+            &tx,
+        )?;
+        if existing_reviews
             .iter()
-            .clone()
-            .map(|r| r.host_name.as_str())
-            .collect();
+            .any(|existing_review| existing_review.content_hash() == content_hash)
+        {
+            log::debug!("Skipping known review: {}", content_hash);
+            continue;
+        }
+
         let package = package::index::get(
             &package::index::Fields {
-                package_name: Some(&review.package.name),
-                package_version: Some(&review.package.version),
-                registry_host_names: Some(registry_host_names),
+                content_hash: Some(&review.package.content_hash()),
                 ..Default::default()
             },
             &tx,
@@ -371,12 +730,34 @@ pub fn merge(
                 &comment.summary,
                 &comment.message,
                 &comment.selection,
+                &comment.in_reply_to,
                 &tx,
             )?;
             new_comments.insert(comment);
         }
 
-        let review = insert(&new_comments, &peer, &package, &tx)?;
+        let existing_review = existing_reviews
+            .into_iter()
+            .find(|existing_review| existing_review.package.id == package.id);
+        let review = match existing_review {
+            Some(mut existing_review) => {
+                existing_review.comments = new_comments;
+                existing_review.criteria = review.criteria;
+                existing_review.package_security = review.package_security;
+                existing_review.review_confidence = review.review_confidence;
+                update(&existing_review, &tx)?;
+                existing_review
+            }
+            None => insert(
+                &new_comments,
+                &review.criteria,
+                &review.package_security,
+                &review.review_confidence,
+                &peer,
+                &package,
+                &tx,
+            )?,
+        };
         new_reviews.insert(review);
     }
     Ok(new_reviews)
@@ -424,12 +805,18 @@ mod tests {
 
             let review_1 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_1,
                 &tx,
             )?;
             let review_2 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_2,
                 &tx,
@@ -455,6 +842,9 @@ mod tests {
             let root_peer = peer::index::get_root(&tx)?.unwrap();
             let review_1 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_1,
                 &tx,
@@ -483,6 +873,9 @@ mod tests {
             let root_peer = peer::index::get_root(&tx)?.unwrap();
             insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_1,
                 &tx,
@@ -501,6 +894,119 @@ mod tests {
             assert_eq!(result, expected);
             Ok(())
         }
+        #[test]
+        fn test_criteria_and_comments_loaded() -> Result<()> {
+            let mut store = crate::store::Store::from_tmp()?;
+            let tx = store.get_transaction()?;
+
+            let package_1 = get_package("package_1", &tx)?;
+            let root_peer = peer::index::get_root(&tx)?.unwrap();
+            let mut comments = std::collections::BTreeSet::new();
+            for message in &["test_message_1", "test_message_2"] {
+                comments.insert(comment::index::insert(
+                    &std::path::PathBuf::from("test_path"),
+                    &common::Summary::Pass,
+                    message,
+                    &None,
+                    &None,
+                    &tx,
+                )?);
+            }
+            let review_1 = insert(
+                &comments,
+                &maplit::btreeset! {"safe-to-run".to_string(), "safe-to-deploy".to_string()},
+                &None,
+                &None,
+                &root_peer,
+                &package_1,
+                &tx,
+            )?;
+
+            let expected = vec![review_1];
+            let result = get(&Fields::default(), &tx)?;
+            assert_eq!(result, expected);
+            Ok(())
+        }
+    }
+
+    mod get_by_packages {
+        use super::*;
+
+        #[test]
+        fn test_reviews_grouped_by_package() -> Result<()> {
+            let mut store = crate::store::Store::from_tmp()?;
+            let tx = store.get_transaction()?;
+
+            let package_1 = get_package("package_1", &tx)?;
+            let _package_2 = get_package("package_2", &tx)?;
+            let root_peer = peer::index::get_root(&tx)?.unwrap();
+            let review_1 = insert(
+                &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
+                &root_peer,
+                &package_1,
+                &tx,
+            )?;
+
+            let result = get_by_packages(
+                &maplit::btreeset! {
+                    ("TEST_PACKAGE_NAME_package_1", "test_package_version"),
+                    ("test_package_name_package_2", "test_package_version"),
+                    ("test_package_name_package_3", "test_package_version"),
+                },
+                "test_registry_host_name",
+                &tx,
+            )?;
+
+            let expected = maplit::btreemap! {
+                (
+                    "TEST_PACKAGE_NAME_package_1".to_string(),
+                    "test_package_version".to_string(),
+                ) => vec![review_1],
+                (
+                    "test_package_name_package_2".to_string(),
+                    "test_package_version".to_string(),
+                ) => vec![],
+                (
+                    "test_package_name_package_3".to_string(),
+                    "test_package_version".to_string(),
+                ) => vec![],
+            };
+            assert_eq!(result, expected);
+            Ok(())
+        }
+    }
+
+    mod update {
+        use super::*;
+
+        #[test]
+        fn test_criteria_replaced() -> Result<()> {
+            let mut store = crate::store::Store::from_tmp()?;
+            let tx = store.get_transaction()?;
+
+            let package_1 = get_package("package_1", &tx)?;
+            let root_peer = peer::index::get_root(&tx)?.unwrap();
+            let mut review_1 = insert(
+                &std::collections::BTreeSet::<comment::Comment>::new(),
+                &maplit::btreeset! {"safe-to-run".to_string()},
+                &None,
+                &None,
+                &root_peer,
+                &package_1,
+                &tx,
+            )?;
+
+            review_1.criteria = maplit::btreeset! {"safe-to-deploy".to_string()};
+            update(&review_1, &tx)?;
+
+            let expected = vec![review_1];
+            let result = get(&Fields::default(), &tx)?;
+            assert_eq!(result, expected);
+            Ok(())
+        }
     }
 
     mod remove {
@@ -518,12 +1024,18 @@ mod tests {
 
             let review_1 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_1,
                 &tx,
             )?;
             let review_2 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_2,
                 &tx,
@@ -556,12 +1068,18 @@ mod tests {
 
             let review_1 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_1,
                 &tx,
             )?;
             let _review_2 = insert(
                 &std::collections::BTreeSet::<comment::Comment>::new(),
+                &std::collections::BTreeSet::new(),
+                &None,
+                &None,
                 &root_peer,
                 &package_2,
                 &tx,
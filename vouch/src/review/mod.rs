@@ -9,11 +9,14 @@ pub mod index;
 pub mod tool;
 pub mod workspace;
 
-pub use crate::review::common::{Review, Summary};
+pub use crate::review::common::{Confidence, Review, Summary};
 
 pub struct ReviewAnalysis {
     pub count_fail_comments: i32,
     pub count_warn_comments: i32,
+
+    /// The reviewer's verdict if given, otherwise the worst comment summary.
+    pub summary: Summary,
 }
 
 pub fn analyse(review: &Review) -> Result<ReviewAnalysis> {
@@ -31,9 +34,16 @@ pub fn analyse(review: &Review) -> Result<ReviewAnalysis> {
             sum
         }
     });
+    let summary = match &review.package_security {
+        Some(package_security) => package_security.clone(),
+        None if count_fail_comments > 0 => Summary::Fail,
+        None if count_warn_comments > 0 => Summary::Warn,
+        None => Summary::Pass,
+    };
     Ok(ReviewAnalysis {
         count_fail_comments,
         count_warn_comments,
+        summary,
     })
 }
 
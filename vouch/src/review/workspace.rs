@@ -33,16 +33,17 @@ fn archive_file_name(archive_type: common::fs::archive::ArchiveType) -> Result<S
 
 /// Ensure review workspace setup is complete.
 ///
-/// Download and unpack package for review.
+/// Download and unpack package for review under the given data paths.
 /// If ongoing workspace exists, return manifest.
 pub fn ensure(
     package_name: &str,
     package_version: &str,
     registry_host_name: &str,
     artifact_url: &url::Url,
+    paths: &common::fs::DataPaths,
 ) -> Result<Manifest> {
     if let Some(workspace_manifest) =
-        get_existing(&package_name, &package_version, &registry_host_name)?
+        get_existing(&package_name, &package_version, &registry_host_name, &paths)?
     {
         return Ok(workspace_manifest);
     }
@@ -56,8 +57,12 @@ pub fn ensure(
         ));
     }
 
-    let package_unique_directory =
-        setup_unique_package_directory(&package_name, &package_version, &registry_host_name)?;
+    let package_unique_directory = setup_unique_package_directory(
+        &package_name,
+        &package_version,
+        &registry_host_name,
+        &paths,
+    )?;
     let archive_path = package_unique_directory.join(archive_file_name(archive_type)?);
 
     common::fs::archive::download(&artifact_url, &archive_path)?;
@@ -118,9 +123,10 @@ pub fn get_existing(
     package_name: &str,
     package_version: &str,
     registry_host_name: &str,
+    paths: &common::fs::DataPaths,
 ) -> Result<Option<Manifest>> {
     let package_unique_directory =
-        get_unique_package_directory(&package_name, &package_version, &registry_host_name)?;
+        get_unique_package_directory(&package_name, &package_version, &registry_host_name, &paths)?;
     let manifest_path = get_manifest_path(&package_unique_directory);
     if manifest_path.is_file() {
         Ok(Some(read_manifest(&manifest_path)?))
@@ -133,10 +139,10 @@ fn get_unique_package_directory(
     package_name: &str,
     package_version: &str,
     registry_host_name: &str,
+    paths: &common::fs::DataPaths,
 ) -> Result<std::path::PathBuf> {
-    let data_paths = common::fs::DataPaths::new()?;
     let package_unique_directory =
-        data_paths
+        paths
             .ongoing_reviews_directory
             .join(review::fs::get_unique_package_path(
                 &package_name,
@@ -151,9 +157,10 @@ fn setup_unique_package_directory(
     package_name: &str,
     package_version: &str,
     registry_host_name: &str,
+    paths: &common::fs::DataPaths,
 ) -> Result<std::path::PathBuf> {
     let package_unique_directory =
-        get_unique_package_directory(&package_name, &package_version, &registry_host_name)?;
+        get_unique_package_directory(&package_name, &package_version, &registry_host_name, &paths)?;
     std::fs::create_dir_all(&package_unique_directory).context(format!(
         "Can't create directory: {}",
         package_unique_directory.display()
@@ -287,9 +294,54 @@ mod tests {
         assert_eq!(result, expected);
         Ok(())
     }
+
+    #[test]
+    fn test_ensure_downloads_and_extracts_archive() -> Result<()> {
+        let tmp_dir = tempdir::TempDir::new("vouch_test_workspace")?;
+        let paths = common::fs::DataPaths::from_root_directory(&tmp_dir.path().to_path_buf())?;
+        let archive = crate::common::fixture::get_tar_gz_archive(&[
+            ("package/index.js", b"module.exports = {};\n", 0o644),
+            ("package/lib/util.js", b"exports.util = 1;\n", 0o644),
+        ])?;
+        let archive_hash = blake3::hash(&archive).to_hex().to_string();
+        let server = crate::common::fixture::Server::new(maplit::btreemap! {
+            "/fixture-workspace/-/fixture-workspace-1.0.0.tgz".to_string() => archive,
+        })?;
+        let artifact_url = server.url("/fixture-workspace/-/fixture-workspace-1.0.0.tgz")?;
+
+        let manifest = ensure(
+            "fixture-workspace",
+            "1.0.0",
+            "registry.fixture.test",
+            &artifact_url,
+            &paths,
+        )?;
+        assert_eq!(manifest.artifact_hash, archive_hash);
+        assert!(manifest.workspace_path.ends_with("fixture-workspace-1.0.0"));
+        assert_eq!(
+            std::fs::read_to_string(manifest.workspace_path.join("lib").join("util.js"))?,
+            "exports.util = 1;\n"
+        );
+        assert!(!manifest.artifact_path.exists());
+
+        // Existing workspace is reused without downloading again.
+        drop(server);
+        let existing = ensure(
+            "fixture-workspace",
+            "1.0.0",
+            "registry.fixture.test",
+            &artifact_url,
+            &paths,
+        )?;
+        assert_eq!(existing, manifest);
+
+        remove(&manifest, &paths)?;
+        assert!(!manifest.workspace_path.exists());
+        Ok(())
+    }
 }
 
-pub fn remove(workspace_manifest: &Manifest) -> Result<()> {
+pub fn remove(workspace_manifest: &Manifest, paths: &common::fs::DataPaths) -> Result<()> {
     log::debug!(
         "Removing workspace directory: {}",
         workspace_manifest.workspace_path.display()
@@ -304,7 +356,6 @@ pub fn remove(workspace_manifest: &Manifest) -> Result<()> {
         std::fs::remove_file(&workspace_manifest.manifest_path)?;
     }
 
-    let paths = common::fs::DataPaths::new()?;
     common::fs::remove_empty_directories(
         &workspace_manifest.workspace_path,
         &paths.ongoing_reviews_directory,
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{format_err, Result};
 
 use crate::review;
 use rusqlite;
@@ -8,26 +8,84 @@ use crate::package;
 use crate::peer;
 use crate::registry;
 
+use super::schema;
+
 pub struct Index {
     pub db: rusqlite::Connection,
+
+    // Holds migrated copies of peer indexes. Must be dropped after the database connection.
+    _tmp_directory: Option<tempdir::TempDir>,
 }
 
 /// An interface for the SQLite database.
 ///
 /// This structure interfaces between SQL index structures and application wide structures.
 impl Index {
-    /// Load root index.
+    /// Load root index. Upgrades the index schema in place if required.
     pub fn from_root() -> Result<Self> {
+        let mut db = peer::fs::get_root_database()?;
+        {
+            let tx = StoreTransaction::new(db.transaction()?)?;
+            schema::migrate(&tx)?;
+            tx.commit_index()?;
+        }
         Ok(Self {
-            db: peer::fs::get_root_database()?,
+            db,
+            _tmp_directory: None,
         })
     }
 
     /// Load the index of a given peer.
+    ///
+    /// Peer indexes with an older schema are migrated within a temporary copy.
+    /// The peer's own index file is never modified.
     pub fn from_peer(peer_branch: &Vec<peer::Peer>) -> Result<Self> {
-        Ok(Self {
-            db: peer::fs::get_peer_database(&peer_branch)?,
-        })
+        let peer = peer_branch
+            .last()
+            .ok_or(format_err!("Invalid empty peer branch."))?;
+        let path = peer::fs::get_peer_database_path(&peer_branch)?;
+
+        let mut db = rusqlite::Connection::open(&path)?;
+        let version = {
+            let tx = StoreTransaction::new(db.transaction()?)?;
+            schema::get_version(&tx)?
+        };
+
+        match version {
+            Some(version) if version > schema::SCHEMA_VERSION => Err(format_err!(
+                "Peer index schema version ({found}) is newer than the version supported by \
+                this version of Vouch ({supported}). Please upgrade Vouch to merge reviews \
+                from peer: {git_url}",
+                found = version,
+                supported = schema::SCHEMA_VERSION,
+                git_url = peer.git_url
+            )),
+            Some(version) if version < schema::SCHEMA_VERSION => {
+                log::debug!(
+                    "Migrating copy of peer index (schema version {}): {}",
+                    version,
+                    peer.git_url
+                );
+                let tmp_directory = tempdir::TempDir::new("vouch_peer_index")?;
+                let tmp_path = tmp_directory.path().join("index.db");
+                std::fs::copy(&path, &tmp_path)?;
+
+                let mut db = rusqlite::Connection::open(&tmp_path)?;
+                {
+                    let tx = StoreTransaction::new(db.transaction()?)?;
+                    schema::migrate(&tx)?;
+                    tx.commit_index()?;
+                }
+                Ok(Self {
+                    db,
+                    _tmp_directory: Some(tmp_directory),
+                })
+            }
+            _ => Ok(Self {
+                db,
+                _tmp_directory: None,
+            }),
+        }
     }
 
     /// Instantiate an in-memory index. Useful for tests.
@@ -35,26 +93,92 @@ impl Index {
     pub fn in_memory() -> Result<Self> {
         Ok(Self {
             db: rusqlite::Connection::open_in_memory()?,
+            _tmp_directory: None,
         })
     }
 }
 
+/// Merge an incoming peer index into another index.
+///
+/// Peers are identified by git URL and reviews by originating peer git URL and content hash. Peers
+/// and reviews reached through multiple paths of the peer tree are merged once.
+///
+/// Blocked peers, and sub-peers and reviews excluded by the given peer options, are skipped.
+/// Packages and registries left unreferenced as a result are removed by `clean`.
 pub fn merge(
     incoming_root_git_url: &crate::common::GitUrl,
+    options: &crate::common::config::PeerOptions,
+    blocklist: &crate::common::config::Blocklist,
     incoming_tx: &StoreTransaction,
     tx: &StoreTransaction,
 ) -> Result<()> {
     registry::index::merge(&incoming_tx, &tx)?;
-    peer::index::merge(&incoming_root_git_url, &incoming_tx, &tx)?;
+    peer::index::merge(
+        &incoming_root_git_url,
+        &options,
+        &blocklist,
+        &incoming_tx,
+        &tx,
+    )?;
     package::index::merge(&incoming_tx, &tx)?;
-    review::index::merge(&incoming_root_git_url, &incoming_tx, &tx)?;
-
-    // TODO: Remove unused rows after inserting reviews. Add index::clean.
+    review::index::merge(
+        &incoming_root_git_url,
+        &options,
+        &blocklist,
+        &incoming_tx,
+        &tx,
+    )?;
     Ok(())
 }
 
+/// Number of orphaned rows removed from each table.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct CleanSummary {
+    pub packages: usize,
+    pub registries: usize,
+    pub comments: usize,
+}
+
+/// Remove packages, registries and comments which are no longer referenced by any review.
+pub fn clean(tx: &StoreTransaction) -> Result<CleanSummary> {
+    // Drop links left behind by removed reviews and packages first.
+    tx.index_tx().execute(
+        "DELETE FROM review_comment WHERE review_id NOT IN (SELECT id FROM review)",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        "DELETE FROM review_criterion WHERE review_id NOT IN (SELECT id FROM review)",
+        rusqlite::NO_PARAMS,
+    )?;
+    let packages = tx.index_tx().execute(
+        "DELETE FROM package WHERE id NOT IN (SELECT package_id FROM review)",
+        rusqlite::NO_PARAMS,
+    )?;
+    tx.index_tx().execute(
+        "DELETE FROM package_registry WHERE package_id NOT IN (SELECT id FROM package)",
+        rusqlite::NO_PARAMS,
+    )?;
+    let registries = tx.index_tx().execute(
+        "DELETE FROM registry WHERE id NOT IN (SELECT registry_id FROM package_registry)",
+        rusqlite::NO_PARAMS,
+    )?;
+    let comments = tx.index_tx().execute(
+        "DELETE FROM comment WHERE id NOT IN (SELECT comment_id FROM review_comment)",
+        rusqlite::NO_PARAMS,
+    )?;
+
+    let summary = CleanSummary {
+        packages,
+        registries,
+        comments,
+    };
+    log::debug!("Removed orphaned index rows: {:?}", summary);
+    Ok(summary)
+}
+
 /// Setup database schema. Insert root peer.
 pub fn setup(tx: &StoreTransaction) -> Result<()> {
+    schema::setup(&tx)?;
     peer::index::setup(&tx)?;
     registry::index::setup(&tx)?;
     package::index::setup(&tx)?;
@@ -68,3 +192,292 @@ pub fn setup_in_memory(index: &mut Index) -> Result<()> {
     tx.commit_index()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::ContentHash;
+
+    #[test]
+    fn test_clean_removes_orphaned_rows() -> Result<()> {
+        let mut store = crate::store::Store::from_tmp()?;
+        let tx = store.get_transaction()?;
+        let root_peer = peer::index::get_root(&tx)?.unwrap();
+
+        let mut packages = Vec::new();
+        for tag in vec!["reviewed", "orphaned"] {
+            let registry = registry::index::insert(
+                "test_registry_host_name",
+                &url::Url::parse("http://localhost/test_registry_human_url")?,
+                &url::Url::parse(&format!("http://localhost/test_archive_url_{}", tag))?,
+                &tx,
+            )?;
+            packages.push(package::index::insert(
+                &format!("test_package_name_{}", tag),
+                "test_package_version",
+                &maplit::btreeset! {registry},
+                "test_source_code_hash",
+                &tx,
+            )?);
+        }
+
+        let comment = review::comment::index::insert(
+            &std::path::PathBuf::from("setup.py"),
+            &review::Summary::Pass,
+            "test_message",
+            &None,
+            &None,
+            &tx,
+        )?;
+        review::comment::index::insert(
+            &std::path::PathBuf::from("setup.py"),
+            &review::Summary::Fail,
+            "orphaned_test_message",
+            &None,
+            &None,
+            &tx,
+        )?;
+        review::index::insert(
+            &maplit::btreeset! {comment},
+            &maplit::btreeset! {},
+            &None,
+            &None,
+            &root_peer,
+            &packages[0],
+            &tx,
+        )?;
+
+        let result = clean(&tx)?;
+        let expected = CleanSummary {
+            packages: 1,
+            registries: 1,
+            comments: 1,
+        };
+        assert_eq!(result, expected);
+        assert_eq!(clean(&tx)?, CleanSummary::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_skips_known_reviews() -> Result<()> {
+        let mut incoming_store = crate::store::Store::from_tmp()?;
+        let incoming_tx = incoming_store.get_transaction()?;
+        let incoming_root_peer = peer::index::get_root(&incoming_tx)?.unwrap();
+        let registry = registry::index::insert(
+            "test_registry_host_name",
+            &url::Url::parse("http://localhost/test_registry_human_url")?,
+            &url::Url::parse("http://localhost/test_archive_url")?,
+            &incoming_tx,
+        )?;
+        let package = package::index::insert(
+            "test_package_name",
+            "test_package_version",
+            &maplit::btreeset! {registry},
+            "test_source_code_hash",
+            &incoming_tx,
+        )?;
+        let comment = review::comment::index::insert(
+            &std::path::PathBuf::from("setup.py"),
+            &review::Summary::Fail,
+            "test_message",
+            &None,
+            &None,
+            &incoming_tx,
+        )?;
+        let incoming_review = review::index::insert(
+            &maplit::btreeset! {comment},
+            &maplit::btreeset! {},
+            &None,
+            &None,
+            &incoming_root_peer,
+            &package,
+            &incoming_tx,
+        )?;
+
+        let mut store = crate::store::Store::from_tmp()?;
+        let tx = store.get_transaction()?;
+        let mut root_peer = peer::index::get_root(&tx)?.unwrap();
+        let git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/user/reviews")?;
+        peer::index::insert("user", &git_url, Some(&mut root_peer), &tx)?;
+
+        merge(
+            &git_url,
+            &Default::default(),
+            &Default::default(),
+            &incoming_tx,
+            &tx,
+        )?;
+        merge(
+            &git_url,
+            &Default::default(),
+            &Default::default(),
+            &incoming_tx,
+            &tx,
+        )?;
+
+        let result = review::index::get(&review::index::Fields::default(), &tx)?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].content_hash(), incoming_review.content_hash());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_skips_blocked_peers() -> Result<()> {
+        let mut incoming_store = crate::store::Store::from_tmp()?;
+        let incoming_tx = incoming_store.get_transaction()?;
+        let mut incoming_root_peer = peer::index::get_root(&incoming_tx)?.unwrap();
+        let blocked_git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/spammer/reviews")?;
+        let blocked_peer = peer::index::insert(
+            "spammer",
+            &blocked_git_url,
+            Some(&mut incoming_root_peer),
+            &incoming_tx,
+        )?;
+        let registry = registry::index::insert(
+            "test_registry_host_name",
+            &url::Url::parse("http://localhost/test_registry_human_url")?,
+            &url::Url::parse("http://localhost/test_archive_url")?,
+            &incoming_tx,
+        )?;
+        let package = package::index::insert(
+            "test_package_name",
+            "test_package_version",
+            &maplit::btreeset! {registry},
+            "test_source_code_hash",
+            &incoming_tx,
+        )?;
+        review::index::insert(
+            &maplit::btreeset! {},
+            &maplit::btreeset! {},
+            &Some(review::Summary::Pass),
+            &None,
+            &blocked_peer,
+            &package,
+            &incoming_tx,
+        )?;
+
+        let mut store = crate::store::Store::from_tmp()?;
+        let tx = store.get_transaction()?;
+        let mut root_peer = peer::index::get_root(&tx)?.unwrap();
+        let git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/user/reviews")?;
+        peer::index::insert("user", &git_url, Some(&mut root_peer), &tx)?;
+
+        let blocklist = crate::common::config::Blocklist {
+            peers: maplit::btreeset! {blocked_git_url.to_string()},
+        };
+        merge(&git_url, &Default::default(), &blocklist, &incoming_tx, &tx)?;
+
+        let peers = peer::index::get(
+            &peer::index::Fields {
+                git_url: Some(&blocked_git_url),
+                ..Default::default()
+            },
+            &tx,
+        )?;
+        assert!(peers.is_empty());
+        let result = review::index::get(&review::index::Fields::default(), &tx)?;
+        assert!(result.is_empty());
+        Ok(())
+    }
+
+    /// Insert a review by a sub-peer of the incoming root peer.
+    fn insert_transitive_review(
+        git_url: &crate::common::GitUrl,
+        package_security: review::Summary,
+        incoming_tx: &StoreTransaction,
+    ) -> Result<()> {
+        let mut incoming_root_peer = peer::index::get_root(&incoming_tx)?.unwrap();
+        let sub_peer = peer::index::insert(
+            "reviewer",
+            &git_url,
+            Some(&mut incoming_root_peer),
+            &incoming_tx,
+        )?;
+        let registry = registry::index::insert(
+            "test_registry_host_name",
+            &url::Url::parse("http://localhost/test_registry_human_url")?,
+            &url::Url::parse("http://localhost/test_archive_url")?,
+            &incoming_tx,
+        )?;
+        let package = package::index::insert(
+            "test_package_name",
+            "test_package_version",
+            &maplit::btreeset! {registry},
+            "test_source_code_hash",
+            &incoming_tx,
+        )?;
+        review::index::insert(
+            &maplit::btreeset! {},
+            &maplit::btreeset! {},
+            &Some(package_security),
+            &None,
+            &sub_peer,
+            &package,
+            &incoming_tx,
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_updates_changed_transitive_review() -> Result<()> {
+        let sub_peer_git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/reviewer/reviews")?;
+
+        let mut store = crate::store::Store::from_tmp()?;
+        let tx = store.get_transaction()?;
+        let mut root_peer = peer::index::get_root(&tx)?.unwrap();
+        let git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/user/reviews")?;
+        peer::index::insert("user", &git_url, Some(&mut root_peer), &tx)?;
+
+        for package_security in vec![review::Summary::Pass, review::Summary::Fail] {
+            let mut incoming_store = crate::store::Store::from_tmp()?;
+            let incoming_tx = incoming_store.get_transaction()?;
+            insert_transitive_review(&sub_peer_git_url, package_security, &incoming_tx)?;
+            merge(
+                &git_url,
+                &Default::default(),
+                &Default::default(),
+                &incoming_tx,
+                &tx,
+            )?;
+        }
+
+        let result = review::index::get(&review::index::Fields::default(), &tx)?;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].peer.git_url, sub_peer_git_url);
+        assert_eq!(result[0].package_security, Some(review::Summary::Fail));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_skips_reviews_beyond_max_depth() -> Result<()> {
+        let sub_peer_git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/reviewer/reviews")?;
+        let mut incoming_store = crate::store::Store::from_tmp()?;
+        let incoming_tx = incoming_store.get_transaction()?;
+        insert_transitive_review(&sub_peer_git_url, review::Summary::Pass, &incoming_tx)?;
+
+        // The sub-peer is also followed directly, so it is already in the index.
+        let mut store = crate::store::Store::from_tmp()?;
+        let tx = store.get_transaction()?;
+        let mut root_peer = peer::index::get_root(&tx)?.unwrap();
+        let git_url: crate::common::GitUrl =
+            std::convert::TryFrom::try_from("https://github.com/user/reviews")?;
+        peer::index::insert("user", &git_url, Some(&mut root_peer), &tx)?;
+        peer::index::insert("reviewer", &sub_peer_git_url, Some(&mut root_peer), &tx)?;
+
+        let options = crate::common::config::PeerOptions {
+            max_depth: Some(0),
+            ..Default::default()
+        };
+        merge(&git_url, &options, &Default::default(), &incoming_tx, &tx)?;
+
+        let result = review::index::get(&review::index::Fields::default(), &tx)?;
+        assert!(result.is_empty());
+        Ok(())
+    }
+}
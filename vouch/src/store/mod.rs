@@ -3,6 +3,7 @@ use crate::peer;
 use anyhow::Result;
 
 pub mod index;
+mod schema;
 
 pub struct Store {
     index: index::Index,
@@ -0,0 +1,608 @@
+use anyhow::{format_err, Result};
+
+use crate::common::index::ID;
+use crate::common::StoreTransaction;
+
+/// Current index schema version. Increment when adding a migration.
+pub static SCHEMA_VERSION: i64 = 6;
+
+/// Index schema migrations.
+///
+/// The migration at position `i` upgrades schema version `i + 1` to version `i + 2`.
+static MIGRATIONS: &[fn(&StoreTransaction) -> Result<()>] = &[
+    migrate_normalize_relations,
+    migrate_add_review_criteria,
+    migrate_add_review_verdict,
+    migrate_add_comment_replies,
+    migrate_add_content_hashes,
+];
+
+/// Setup schema version table. Records the current schema version if absent.
+pub fn setup(tx: &StoreTransaction) -> Result<()> {
+    setup_version_table(&tx)?;
+    if get_recorded_version(&tx)?.is_none() {
+        set_version(SCHEMA_VERSION, &tx)?;
+    }
+    Ok(())
+}
+
+fn setup_version_table(tx: &StoreTransaction) -> Result<()> {
+    tx.index_tx().execute(
+        r"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version         INTEGER NOT NULL
+        )",
+        rusqlite::NO_PARAMS,
+    )?;
+    Ok(())
+}
+
+fn table_exists(name: &str, tx: &StoreTransaction) -> Result<bool> {
+    let count: i64 = tx.index_tx().query_row(
+        r"
+        SELECT count(*)
+        FROM sqlite_master
+        WHERE type = 'table' AND name = ?1
+        ",
+        rusqlite::params![name],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+fn get_recorded_version(tx: &StoreTransaction) -> Result<Option<i64>> {
+    Ok(tx.index_tx().query_row(
+        "SELECT max(version) FROM schema_version",
+        rusqlite::NO_PARAMS,
+        |row| row.get(0),
+    )?)
+}
+
+fn set_version(version: i64, tx: &StoreTransaction) -> Result<()> {
+    tx.index_tx()
+        .execute("DELETE FROM schema_version", rusqlite::NO_PARAMS)?;
+    tx.index_tx().execute(
+        "INSERT INTO schema_version (version) VALUES (?1)",
+        rusqlite::params![version],
+    )?;
+    Ok(())
+}
+
+/// Returns the index schema version. Returns None if the index has not been setup.
+///
+/// Indexes created before schema versioning was introduced are version 1.
+pub fn get_version(tx: &StoreTransaction) -> Result<Option<i64>> {
+    if table_exists("schema_version", &tx)? {
+        return get_recorded_version(&tx);
+    }
+    if table_exists("peer", &tx)? {
+        return Ok(Some(1));
+    }
+    Ok(None)
+}
+
+/// Upgrade index schema in place to the current schema version.
+///
+/// Raises an error if the index was written by a newer version of Vouch.
+pub fn migrate(tx: &StoreTransaction) -> Result<()> {
+    let mut version = match get_version(&tx)? {
+        Some(version) => version,
+        None => {
+            log::debug!("Index not setup. Skipping schema migration.");
+            return Ok(());
+        }
+    };
+    if version > SCHEMA_VERSION {
+        return Err(format_err!(
+            "Index schema version ({found}) is newer than the version supported by \
+            this version of Vouch ({supported}). Please upgrade Vouch.",
+            found = version,
+            supported = SCHEMA_VERSION
+        ));
+    }
+
+    setup_version_table(&tx)?;
+    while version < SCHEMA_VERSION {
+        log::info!(
+            "Migrating index schema from version {} to {}.",
+            version,
+            version + 1
+        );
+        let migration = MIGRATIONS.get((version - 1) as usize).ok_or(format_err!(
+            "Failed to find index schema migration for version: {}",
+            version
+        ))?;
+        migration(&tx)?;
+        version += 1;
+    }
+    set_version(version, &tx)?;
+    Ok(())
+}
+
+/// Read ID lists serialized into a BLOB column. Returns (row ID, ID) pairs.
+fn get_serialized_ids(
+    table_name: &str,
+    column_name: &str,
+    tx: &StoreTransaction,
+) -> Result<Vec<(ID, ID)>> {
+    let mut statement = tx.index_tx().prepare(&format!(
+        "SELECT id, {column_name} FROM {table_name}",
+        column_name = column_name,
+        table_name = table_name
+    ))?;
+    let mut rows = statement.query(rusqlite::NO_PARAMS)?;
+
+    let mut pairs = Vec::new();
+    while let Some(row) = rows.next()? {
+        let id: ID = row.get(0)?;
+        if let Some(blob) = row.get::<_, Option<Vec<u8>>>(1)? {
+            let ids: Vec<ID> = bincode::deserialize(&blob)?;
+            pairs.extend(ids.into_iter().map(|other_id| (id, other_id)));
+        }
+    }
+    Ok(pairs)
+}
+
+/// Version 1 to 2: replace serialized ID list columns with join tables.
+///
+/// Peer child IDs are dropped in favour of parent IDs. Package registry IDs and review comment IDs
+/// move to the `package_registry` and `review_comment` tables.
+fn migrate_normalize_relations(tx: &StoreTransaction) -> Result<()> {
+    let package_registries = get_serialized_ids("package", "registry_ids", &tx)?;
+    let review_comments = get_serialized_ids("review", "comment_ids", &tx)?;
+
+    // Foreign keys are enforced and can not be disabled within a transaction. Rename tables in
+    // legacy mode so that references from other tables are left pointing at the new tables, then
+    // drop the renamed tables which nothing references.
+    tx.index_tx().execute_batch(
+        r"
+        PRAGMA defer_foreign_keys = ON;
+        PRAGMA legacy_alter_table = ON;
+
+        ALTER TABLE peer RENAME TO peer_old;
+        CREATE TABLE peer (
+            id              INTEGER NOT NULL PRIMARY KEY,
+            alias           TEXT NOT NULL UNIQUE,
+            git_url         TEXT NOT NULL UNIQUE,
+            parent_id       INTEGER,
+
+            FOREIGN KEY(parent_id) REFERENCES peer(id)
+        );
+        INSERT INTO peer (id, alias, git_url, parent_id)
+            SELECT id, alias, git_url, parent_id FROM peer_old;
+        CREATE INDEX peer_parent_id_index ON peer(parent_id);
+
+        ALTER TABLE package RENAME TO package_old;
+        CREATE TABLE package (
+            id                         INTEGER NOT NULL PRIMARY KEY,
+            name                       TEXT NOT NULL,
+            version                    TEXT NOT NULL,
+            artifact_hash              TEXT NOT NULL,
+
+            UNIQUE(name, version, artifact_hash)
+        );
+        INSERT INTO package (id, name, version, artifact_hash)
+            SELECT id, name, version, artifact_hash FROM package_old;
+
+        CREATE TABLE package_registry (
+            package_id                 INTEGER NOT NULL,
+            registry_id                INTEGER NOT NULL,
+
+            PRIMARY KEY(package_id, registry_id)
+            FOREIGN KEY(package_id) REFERENCES package(id) ON DELETE CASCADE
+            FOREIGN KEY(registry_id) REFERENCES registry(id)
+        );
+        CREATE INDEX package_registry_registry_id_index ON package_registry(registry_id);
+
+        ALTER TABLE review RENAME TO review_old;
+        CREATE TABLE review (
+            id                    INTEGER NOT NULL PRIMARY KEY,
+            peer_id               INTEGER NOT NULL,
+            package_id            INTEGER NOT NULL,
+
+            UNIQUE(peer_id, package_id)
+            FOREIGN KEY(peer_id) REFERENCES peer(id)
+            CONSTRAINT fk_package
+                FOREIGN KEY (package_id)
+                REFERENCES package(id)
+                ON DELETE CASCADE
+        );
+        INSERT INTO review (id, peer_id, package_id)
+            SELECT id, peer_id, package_id FROM review_old;
+        CREATE INDEX review_package_id_index ON review(package_id);
+
+        CREATE TABLE review_comment (
+            review_id             INTEGER NOT NULL,
+            comment_id            INTEGER NOT NULL,
+
+            PRIMARY KEY(review_id, comment_id)
+            FOREIGN KEY(review_id) REFERENCES review(id) ON DELETE CASCADE
+            FOREIGN KEY(comment_id) REFERENCES comment(id) ON DELETE CASCADE
+        );
+        CREATE INDEX review_comment_comment_id_index ON review_comment(comment_id);
+
+        DROP TABLE review_old;
+        DROP TABLE package_old;
+        DROP TABLE peer_old;
+        PRAGMA legacy_alter_table = OFF;
+        ",
+    )?;
+
+    for (package_id, registry_id) in package_registries {
+        tx.index_tx().execute(
+            "INSERT OR IGNORE INTO package_registry (package_id, registry_id) VALUES (?1, ?2)",
+            rusqlite::params![package_id, registry_id],
+        )?;
+    }
+    for (review_id, comment_id) in review_comments {
+        tx.index_tx().execute(
+            "INSERT OR IGNORE INTO review_comment (review_id, comment_id) VALUES (?1, ?2)",
+            rusqlite::params![review_id, comment_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Version 2 to 3: add review criteria table.
+fn migrate_add_review_criteria(tx: &StoreTransaction) -> Result<()> {
+    tx.index_tx().execute_batch(
+        r"
+        CREATE TABLE review_criterion (
+            review_id             INTEGER NOT NULL,
+            name                  TEXT NOT NULL,
+
+            PRIMARY KEY(review_id, name)
+            FOREIGN KEY(review_id) REFERENCES review(id) ON DELETE CASCADE
+        );
+        ",
+    )?;
+    Ok(())
+}
+
+/// Version 3 to 4: add review verdict and confidence columns.
+fn migrate_add_review_verdict(tx: &StoreTransaction) -> Result<()> {
+    tx.index_tx().execute_batch(
+        r"
+        ALTER TABLE review ADD COLUMN package_security TEXT;
+        ALTER TABLE review ADD COLUMN review_confidence TEXT;
+        ",
+    )?;
+    Ok(())
+}
+
+/// Version 4 to 5: add comment reply references.
+fn migrate_add_comment_replies(tx: &StoreTransaction) -> Result<()> {
+    tx.index_tx()
+        .execute_batch("ALTER TABLE comment ADD COLUMN in_reply_to TEXT;")?;
+    Ok(())
+}
+
+/// Version 5 to 6: add content hash columns to package, comment and review tables.
+///
+/// Hashes are derived from table rows by this migration alone, independently of the current
+/// content hash implementations. Content hashes identify reviews across peers, so this migration
+/// must always give the same hashes.
+fn migrate_add_content_hashes(tx: &StoreTransaction) -> Result<()> {
+    tx.index_tx().execute_batch(
+        r"
+        ALTER TABLE package ADD COLUMN content_hash TEXT;
+        CREATE INDEX package_content_hash_index ON package(content_hash);
+        ALTER TABLE comment ADD COLUMN content_hash TEXT;
+        CREATE INDEX comment_content_hash_index ON comment(content_hash);
+        ALTER TABLE review ADD COLUMN content_hash TEXT;
+        CREATE INDEX review_content_hash_index ON review(content_hash);
+        ",
+    )?;
+
+    let mut package_hashes = std::collections::HashMap::<ID, String>::new();
+    {
+        let mut statement = tx
+            .index_tx()
+            .prepare("SELECT id, name, version, artifact_hash FROM package")?;
+        let mut rows = statement.query(rusqlite::NO_PARAMS)?;
+        while let Some(row) = rows.next()? {
+            let package_id: ID = row.get(0)?;
+            let package_hash = get_version_6_content_hash(&serde_json::json!({
+                "name": row.get::<_, String>(1)?,
+                "version": row.get::<_, String>(2)?,
+                "artifact_hash": row.get::<_, String>(3)?,
+            }));
+            package_hashes.insert(package_id, package_hash);
+        }
+    }
+
+    let mut comment_hashes = std::collections::HashMap::<ID, String>::new();
+    {
+        let mut statement = tx.index_tx().prepare(
+            r"
+            SELECT
+                id,
+                path,
+                summary,
+                message,
+                selection_start_line,
+                selection_start_character,
+                selection_end_line,
+                selection_end_character,
+                in_reply_to
+            FROM comment",
+        )?;
+        let mut rows = statement.query(rusqlite::NO_PARAMS)?;
+        while let Some(row) = rows.next()? {
+            let comment_id: ID = row.get(0)?;
+            let selection = match (
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+                row.get::<_, Option<i64>>(6)?,
+                row.get::<_, Option<i64>>(7)?,
+            ) {
+                (None, None, None, None) => serde_json::Value::Null,
+                (Some(start_line), Some(start_character), Some(end_line), Some(end_character)) => {
+                    serde_json::json!({
+                        "start": {"line": start_line, "character": start_character},
+                        "end": {"line": end_line, "character": end_character},
+                    })
+                }
+                _ => {
+                    return Err(format_err!(
+                        "Found incomplete selection for comment: {}",
+                        comment_id
+                    ))
+                }
+            };
+            let comment_hash = get_version_6_content_hash(&serde_json::json!({
+                "summary": row.get::<_, String>(2)?,
+                "path": row.get::<_, String>(1)?,
+                "message": row.get::<_, Option<String>>(3)?,
+                "selection": selection,
+                "in_reply_to": row.get::<_, Option<String>>(8)?,
+            }));
+            comment_hashes.insert(comment_id, comment_hash);
+        }
+    }
+
+    let mut review_hashes = Vec::new();
+    {
+        let mut statement = tx
+            .index_tx()
+            .prepare("SELECT id, package_id, package_security, review_confidence FROM review")?;
+        let mut rows = statement.query(rusqlite::NO_PARAMS)?;
+        while let Some(row) = rows.next()? {
+            let review_id: ID = row.get(0)?;
+            let package_id: ID = row.get(1)?;
+
+            let review_comment_hashes = get_linked_values(
+                "SELECT comment_id FROM review_comment WHERE review_id = ?1",
+                review_id,
+                &tx,
+            )?
+            .into_iter()
+            .filter_map(|comment_id: ID| comment_hashes.get(&comment_id).cloned())
+            .collect::<std::collections::BTreeSet<_>>();
+            let criteria = get_linked_values::<String>(
+                "SELECT name FROM review_criterion WHERE review_id = ?1",
+                review_id,
+                &tx,
+            )?
+            .into_iter()
+            .collect::<std::collections::BTreeSet<_>>();
+
+            let package_hash = package_hashes.get(&package_id).ok_or(format_err!(
+                "Failed to find package for review: {}",
+                review_id
+            ))?;
+            let review_hash = get_version_6_content_hash(&serde_json::json!({
+                "package": package_hash,
+                "comments": review_comment_hashes,
+                "criteria": criteria,
+                "package_security": row.get::<_, Option<String>>(2)?,
+                "review_confidence": row.get::<_, Option<String>>(3)?,
+            }));
+            review_hashes.push((review_id, review_hash));
+        }
+    }
+
+    for (table_name, hashes) in vec![
+        ("package", package_hashes.into_iter().collect::<Vec<_>>()),
+        ("comment", comment_hashes.into_iter().collect()),
+        ("review", review_hashes),
+    ] {
+        for (id, hash) in hashes {
+            tx.index_tx().execute(
+                &format!(
+                    "UPDATE {table_name} SET content_hash = ?1 WHERE id = ?2",
+                    table_name = table_name
+                ),
+                rusqlite::params![hash, id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the blake3 hex digest of the canonical JSON serialization of the given content, as
+/// defined by schema version 6.
+///
+/// Object keys are serialized in sorted order without whitespace.
+fn get_version_6_content_hash(content: &serde_json::Value) -> String {
+    fn write_canonical_json(value: &serde_json::Value, output: &mut Vec<u8>) {
+        match value {
+            serde_json::Value::Array(values) => {
+                output.push(b'[');
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        output.push(b',');
+                    }
+                    write_canonical_json(&value, output);
+                }
+                output.push(b']');
+            }
+            serde_json::Value::Object(map) => {
+                let mut entries = map.iter().collect::<Vec<_>>();
+                entries.sort_by(|(key, _), (other_key, _)| key.cmp(other_key));
+
+                output.push(b'{');
+                for (index, (key, value)) in entries.into_iter().enumerate() {
+                    if index > 0 {
+                        output.push(b',');
+                    }
+                    output.extend(
+                        serde_json::Value::String(key.clone())
+                            .to_string()
+                            .as_bytes(),
+                    );
+                    output.push(b':');
+                    write_canonical_json(&value, output);
+                }
+                output.push(b'}');
+            }
+            value => output.extend(value.to_string().as_bytes()),
+        }
+    }
+
+    let mut canonical = Vec::new();
+    write_canonical_json(&content, &mut canonical);
+    blake3::hash(&canonical).to_hex().as_str().to_string()
+}
+
+/// Returns the first column of all rows selected by the given query for a row ID.
+fn get_linked_values<T: rusqlite::types::FromSql>(
+    sql_query: &str,
+    id: ID,
+    tx: &StoreTransaction,
+) -> Result<Vec<T>> {
+    let mut statement = tx.index_tx().prepare(sql_query)?;
+    let mut rows = statement.query(rusqlite::params![id])?;
+    let mut values = Vec::new();
+    while let Some(row) = rows.next()? {
+        values.push(row.get(0)?);
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::ContentHash;
+
+    /// Setup an index using the schema from before versioning was introduced.
+    fn setup_version_1(tx: &StoreTransaction) -> Result<()> {
+        tx.index_tx().execute_batch(
+            r"
+            CREATE TABLE peer (
+                id              INTEGER NOT NULL PRIMARY KEY,
+                alias           TEXT NOT NULL UNIQUE,
+                git_url         TEXT NOT NULL UNIQUE,
+                parent_id       INTEGER,
+                child_peer_ids  BLOB,
+
+                FOREIGN KEY(parent_id) REFERENCES peer(id)
+            );
+            CREATE TABLE registry (
+                id                         INTEGER NOT NULL PRIMARY KEY,
+                host_name                  TEXT NOT NULL,
+                human_url                  TEXT NOT NULL,
+                artifact_url               TEXT NOT NULL UNIQUE
+            );
+            CREATE TABLE package (
+                id                         INTEGER NOT NULL PRIMARY KEY,
+                name                       TEXT NOT NULL,
+                version                    TEXT NOT NULL,
+                registry_ids               BLOB NOT NULL,
+                artifact_hash              TEXT NOT NULL,
+
+                UNIQUE(name, version, artifact_hash)
+            );
+            CREATE TABLE comment (
+                id                        INTEGER NOT NULL PRIMARY KEY,
+                path                      TEXT NOT NULL,
+                summary                   TEXT NOT NULL,
+                message                   TEXT,
+                selection_start_line      INTEGER,
+                selection_start_character INTEGER,
+                selection_end_line        INTEGER,
+                selection_end_character   INTEGER
+            );
+            CREATE TABLE review (
+                id                    INTEGER NOT NULL PRIMARY KEY,
+                peer_id               INTEGER NOT NULL,
+                package_id            INTEGER NOT NULL,
+                comment_ids           BLOB,
+
+                UNIQUE(peer_id, package_id)
+                FOREIGN KEY(peer_id) REFERENCES peer(id)
+                CONSTRAINT fk_package
+                    FOREIGN KEY (package_id)
+                    REFERENCES package(id)
+                    ON DELETE CASCADE
+            );
+            INSERT INTO peer (id, alias, git_url) VALUES (1, 'root', 'https://localhost');
+            INSERT INTO registry (id, host_name, human_url, artifact_url)
+                VALUES (1, 'pypi.org', 'https://pypi.org/', 'https://pypi.org/archive');
+            INSERT INTO comment (id, path, summary, message)
+                VALUES (1, 'setup.py', 'pass', 'test_message');
+            ",
+        )?;
+        tx.index_tx().execute(
+            r"
+            INSERT INTO package (id, name, version, registry_ids, artifact_hash)
+                VALUES (1, 'numpy', '1.0.0', ?1, 'test_hash')",
+            rusqlite::params![bincode::serialize(&vec![1 as ID])?],
+        )?;
+        tx.index_tx().execute(
+            "INSERT INTO review (id, peer_id, package_id, comment_ids) VALUES (1, 1, 1, ?1)",
+            rusqlite::params![bincode::serialize(&vec![1 as ID])?],
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_unversioned_index() -> Result<()> {
+        let mut db = rusqlite::Connection::open_in_memory()?;
+        let tx = StoreTransaction::new(db.transaction()?)?;
+        setup_version_1(&tx)?;
+
+        migrate(&tx)?;
+        assert_eq!(get_version(&tx)?, Some(SCHEMA_VERSION));
+
+        let reviews = crate::review::index::get(&crate::review::index::Fields::default(), &tx)?;
+        assert_eq!(reviews.len(), 1);
+        let review = &reviews[0];
+        assert_eq!(review.package.name, "numpy");
+        assert_eq!(review.package.registries.len(), 1);
+        assert_eq!(review.comments.len(), 1);
+        assert!(review.criteria.is_empty());
+        assert_eq!(review.package_security, None);
+
+        let result = crate::review::index::get(
+            &crate::review::index::Fields {
+                content_hash: Some(&review.content_hash()),
+                ..Default::default()
+            },
+            &tx,
+        )?;
+        assert_eq!(result.len(), 1);
+
+        // Migrated hashes must not change with the current content hash implementations.
+        let migrated_hash: String = tx.index_tx().query_row(
+            "SELECT content_hash FROM review WHERE id = 1",
+            rusqlite::NO_PARAMS,
+            |row| row.get(0),
+        )?;
+        assert_eq!(
+            migrated_hash,
+            "4e0e8606533618765aaed3c2505110349d50eaf5f466ade8a57ae73d1f061649"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_newer_index_fails() -> Result<()> {
+        let mut store = crate::store::Store::from_tmp()?;
+        let tx = store.get_transaction()?;
+        set_version(SCHEMA_VERSION + 1, &tx)?;
+
+        assert!(migrate(&tx).is_err());
+        Ok(())
+    }
+}
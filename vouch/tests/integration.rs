@@ -0,0 +1,294 @@
+//! End-to-end tests which run the vouch binary as several simulated users.
+//!
+//! Each user has an isolated VOUCH_HOME and HOME. Users publish reviews to local bare git
+//! repositories. Packages are served by a stub process extension from local archives, and
+//! reviews are written by a stub `code` executable in place of VSCode.
+#![cfg(unix)]
+
+use anyhow::{format_err, Result};
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+
+/// Shared test fixtures: stub executables, package archives and remote repositories.
+struct Fixture {
+    root: tempdir::TempDir,
+}
+
+impl Fixture {
+    fn new() -> Result<Self> {
+        let fixture = Self {
+            root: tempdir::TempDir::new("vouch_integration")?,
+        };
+        std::fs::create_dir_all(fixture.bin_directory())?;
+        std::fs::create_dir_all(fixture.registry_directory())?;
+        std::fs::create_dir_all(fixture.remotes_directory())?;
+        fixture.write_stub_extension()?;
+        fixture.write_stub_review_tool()?;
+        Ok(fixture)
+    }
+
+    fn path(&self) -> std::path::PathBuf {
+        self.root.path().to_path_buf()
+    }
+
+    fn bin_directory(&self) -> std::path::PathBuf {
+        self.path().join("bin")
+    }
+
+    fn registry_directory(&self) -> std::path::PathBuf {
+        self.path().join("registry")
+    }
+
+    fn remotes_directory(&self) -> std::path::PathBuf {
+        self.path().join("remotes")
+    }
+
+    /// Writes a process extension which handles registry stub.example.
+    ///
+    /// Package artifacts are served from the fixture registry directory. Every project depends
+    /// on foo 1.0.0 and bar 2.0.0.
+    fn write_stub_extension(&self) -> Result<()> {
+        let script = format!(
+            r#"#!/bin/sh
+dependencies='[{{"name":"foo","version":{{"Ok":"1.0.0"}}}},{{"name":"bar","version":{{"Ok":"2.0.0"}}}}]'
+case "$1" in
+static-data)
+    echo '{{"name":"stub","registry_host_names":["stub.example"]}}'
+    ;;
+registries-package-metadata)
+    echo '[{{"registry_host_name":"stub.example","human_url":"https://stub.example/'"$2"'","artifact_url":"file://{registry}/'"$2-$3"'.tar.gz","is_primary":true}}]'
+    ;;
+identify-local-dependencies)
+    echo '[{{"path":"'"$2"'/stub.lock","registry_host_name":"stub.example","dependencies":'"$dependencies"'}}]'
+    ;;
+identify-file-dependencies)
+    echo '{{"path":"'"$2"'","registry_host_name":"stub.example","dependencies":'"$dependencies"'}}'
+    ;;
+esac
+"#,
+            registry = self.registry_directory().display()
+        );
+        write_executable(&self.bin_directory().join("vouch-stub"), &script)
+    }
+
+    /// Writes a stub `code` executable which gives the active review a pass verdict.
+    fn write_stub_review_tool(&self) -> Result<()> {
+        let script = r#"#!/bin/sh
+for workspace; do :; done
+review="$workspace/.vscode/reviews/local.review"
+sed 's/"packageSecurity": null/"packageSecurity": "pass"/' "$review" > "$review.tmp"
+mv "$review.tmp" "$review"
+"#;
+        write_executable(&self.bin_directory().join("code"), &script)
+    }
+
+    /// Adds a package archive to the fixture registry.
+    fn add_package(&self, name: &str, version: &str) -> Result<()> {
+        let package_directory_name = format!("{}-{}", name, version);
+        let source_directory = self.path().join("sources").join(&package_directory_name);
+        std::fs::create_dir_all(&source_directory)?;
+        std::fs::write(source_directory.join("README"), name)?;
+
+        let archive_path = self
+            .registry_directory()
+            .join(format!("{}.tar.gz", package_directory_name));
+        let archive = std::fs::File::create(&archive_path)?;
+        let encoder = flate2::write::GzEncoder::new(archive, flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        builder.append_dir_all(&package_directory_name, &source_directory)?;
+        builder.into_inner()?.finish()?;
+        Ok(())
+    }
+
+    /// Creates an empty bare git repository and returns its path.
+    fn add_remote(&self, name: &str) -> Result<std::path::PathBuf> {
+        let path = self.remotes_directory().join(format!("{}.git", name));
+        let status = std::process::Command::new("git")
+            .args(vec!["init", "--quiet", "--bare"])
+            .arg(&path)
+            .status()?;
+        if !status.success() {
+            return Err(format_err!("Failed to create remote: {}", path.display()));
+        }
+        Ok(path)
+    }
+
+    /// Sets up and returns a new user. The user publishes to the given remote if any.
+    fn add_user(&self, name: &str, remote: Option<&std::path::PathBuf>) -> Result<User> {
+        let user = User {
+            name: name.to_string(),
+            home_directory: self.path().join("users").join(name),
+            bin_directory: self.bin_directory(),
+        };
+        std::fs::create_dir_all(&user.project_directory())?;
+        std::fs::write(
+            user.home_directory.join(".gitconfig"),
+            format!(
+                "[user]\n\tname = {name}\n\temail = {name}@example.com\n\
+                [init]\n\tdefaultBranch = master\n",
+                name = name
+            ),
+        )?;
+
+        match remote {
+            Some(remote) => user.vouch(&["setup", path_str(&remote)?])?,
+            None => user.vouch(&["setup"])?,
+        };
+        user.vouch(&["config", "review-tool.install-check", "true"])?;
+        Ok(user)
+    }
+}
+
+struct User {
+    name: String,
+    home_directory: std::path::PathBuf,
+    bin_directory: std::path::PathBuf,
+}
+
+impl User {
+    fn project_directory(&self) -> std::path::PathBuf {
+        self.home_directory.join("project")
+    }
+
+    /// Runs vouch as this user from the user's project directory. Returns stdout.
+    fn vouch(&self, args: &[&str]) -> Result<String> {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let path = std::env::join_paths(
+            std::iter::once(self.bin_directory.clone()).chain(std::env::split_paths(&path)),
+        )?;
+
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_vouch"))
+            .args(args)
+            .current_dir(self.project_directory())
+            .env("VOUCH_HOME", self.home_directory.join("vouch"))
+            .env("HOME", &self.home_directory)
+            .env("PATH", path)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env_remove("XDG_CONFIG_HOME")
+            .env_remove("GIT_AUTHOR_NAME")
+            .env_remove("GIT_AUTHOR_EMAIL")
+            .env_remove("GIT_COMMITTER_NAME")
+            .env_remove("GIT_COMMITTER_EMAIL")
+            .stdin(std::process::Stdio::null())
+            .output()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        if !output.status.success() {
+            return Err(format_err!(
+                "User {} failed to run: vouch {}\n{}\n{}",
+                self.name,
+                args.join(" "),
+                stdout,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(stdout)
+    }
+
+    fn review(&self, package_name: &str, package_version: &str) -> Result<String> {
+        self.vouch(&[
+            "review",
+            package_name,
+            package_version,
+            "--extension",
+            "stub",
+            "--yes",
+        ])
+    }
+}
+
+fn write_executable(path: &std::path::PathBuf, content: &str) -> Result<()> {
+    let mut file = std::fs::File::create(&path)?;
+    file.write_all(content.as_bytes())?;
+    file.set_permissions(std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+fn path_str(path: &std::path::PathBuf) -> Result<&str> {
+    path.to_str().ok_or(format_err!(
+        "Failed to parse path into string: {}",
+        path.display()
+    ))
+}
+
+/// Returns the report table row for the given package.
+fn get_table_row<'a>(report: &'a str, package_name: &str) -> Option<&'a str> {
+    report.lines().find(|line| {
+        line.split('|')
+            .nth(1)
+            .map_or(false, |name| name.trim() == package_name)
+    })
+}
+
+#[test]
+fn test_reviews_propagate_through_peers() -> Result<()> {
+    let fixture = Fixture::new()?;
+    fixture.add_package("foo", "1.0.0")?;
+
+    let alice_remote = fixture.add_remote("alice")?;
+    let alice = fixture.add_user("alice", Some(&alice_remote))?;
+    let result = alice.review("foo", "1.0.0")?;
+    assert!(result.contains("Package verdict: pass"), "{}", result);
+    alice.vouch(&["sync"])?;
+
+    let bob_remote = fixture.add_remote("bob")?;
+    let bob = fixture.add_user("bob", Some(&bob_remote))?;
+    bob.vouch(&["peer", "add", path_str(&alice_remote)?])?;
+    bob.vouch(&["sync"])?;
+
+    let carol = fixture.add_user("carol", None)?;
+    carol.vouch(&["peer", "add", path_str(&bob_remote)?])?;
+
+    let result = carol.vouch(&["peer", "list"])?;
+    assert!(result.contains("└── bob"), "{}", result);
+    assert!(result.contains("    └── alice"), "{}", result);
+
+    let result = carol.vouch(&["check", "foo", "1.0.0"])?;
+    assert!(result.contains("Peer: alice"), "{}", result);
+    assert!(result.contains("Verdict: pass"), "{}", result);
+
+    let result = carol.vouch(&["check", "--extension", "stub"])?;
+    let foo_row = get_table_row(&result, "foo").ok_or(format_err!("{}", result))?;
+    assert!(foo_row.contains("PASS"), "{}", result);
+    let bar_row = get_table_row(&result, "bar").ok_or(format_err!("{}", result))?;
+    assert!(bar_row.contains("WARN"), "{}", result);
+    Ok(())
+}
+
+#[test]
+fn test_sync_fetches_new_peer_reviews() -> Result<()> {
+    let fixture = Fixture::new()?;
+    fixture.add_package("foo", "1.0.0")?;
+    fixture.add_package("bar", "2.0.0")?;
+
+    let alice_remote = fixture.add_remote("alice")?;
+    let alice = fixture.add_user("alice", Some(&alice_remote))?;
+    alice.review("foo", "1.0.0")?;
+    alice.vouch(&["sync"])?;
+
+    let bob = fixture.add_user("bob", None)?;
+    bob.vouch(&["peer", "add", path_str(&alice_remote)?])?;
+    bob.review("bar", "2.0.0")?;
+
+    let result = bob.vouch(&["sync"])?;
+    assert!(result.contains("All peers up-to-date."), "{}", result);
+
+    alice.review("bar", "2.0.0")?;
+    alice.vouch(&["sync"])?;
+
+    let result = bob.vouch(&["sync"])?;
+    assert!(!result.contains("All peers up-to-date."), "{}", result);
+
+    let result = bob.vouch(&["check", "bar", "2.0.0"])?;
+    assert!(result.contains("Peer: alice"), "{}", result);
+    assert!(result.contains("Peer: root"), "{}", result);
+
+    let result = bob.vouch(&["check", "--extension", "stub"])?;
+    let bar_row = get_table_row(&result, "bar").ok_or(format_err!("{}", result))?;
+    assert!(bar_row.contains("PASS"), "{}", result);
+    assert_eq!(
+        bar_row.split('|').nth(3).map(|count| count.trim()),
+        Some("2")
+    );
+    Ok(())
+}